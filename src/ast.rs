@@ -1,10 +1,27 @@
-use crate::statements::{Node, Statements};
+use crate::{
+    span::Span,
+    statements::{Node, Statements},
+};
 
-pub struct Programm {
-    pub statements: Vec<Statements>,
+#[derive(Debug, PartialEq, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Programm<'src> {
+    pub statements: Vec<Statements<'src>>,
 }
 
-impl Node for Programm {
+impl Programm<'_> {
+    pub fn into_owned(self) -> Programm<'static> {
+        Programm {
+            statements: self
+                .statements
+                .into_iter()
+                .map(Statements::into_owned)
+                .collect(),
+        }
+    }
+}
+
+impl Node for Programm<'_> {
     fn token_literal(&self) -> String {
         if !self.statements.is_empty() {
             self.statements[0].token_literal()
@@ -12,4 +29,11 @@ impl Node for Programm {
             "".to_string()
         }
     }
+
+    fn span(&self) -> Span {
+        match (self.statements.first(), self.statements.last()) {
+            (Some(first), Some(last)) => first.span().to(last.span()),
+            _ => Span::default(),
+        }
+    }
 }
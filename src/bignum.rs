@@ -0,0 +1,636 @@
+use std::{cmp::Ordering, fmt::Display};
+
+//32 bit limbs so products of two limbs fit in a u64
+const BASE: u64 = 1 << 32;
+//Largest power of ten below BASE, used to convert from and to decimal
+const DECIMAL_BASE: u32 = 1_000_000_000;
+const DECIMAL_DIGITS: usize = 9;
+
+//Arbitrary precision integer, stored as sign and magnitude with the least
+//significant limb first. Always normalized: no leading zero limbs and zero is
+//never negative, so derived equality and hashing work on the value.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct BigInt {
+    negative: bool,
+    limbs: Vec<u32>,
+}
+
+impl BigInt {
+    pub fn zero() -> Self {
+        BigInt::default()
+    }
+
+    //Parses an optionally negative decimal number
+    pub fn parse(text: &str) -> Option<Self> {
+        let (negative, digits) = match text.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, text),
+        };
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+
+        let mut limbs = vec![];
+        //The first chunk is shorter so the others are exactly 9 digits
+        let first = match digits.len() % DECIMAL_DIGITS {
+            0 => DECIMAL_DIGITS,
+            n => n,
+        };
+        let mut start = 0;
+        let mut end = first;
+        while start < digits.len() {
+            let chunk: u32 = digits[start..end].parse().ok()?;
+            let multiplier = 10u32.pow((end - start) as u32);
+            mul_small_add(&mut limbs, multiplier, chunk);
+            start = end;
+            end += DECIMAL_DIGITS;
+        }
+
+        Some(BigInt::from_parts(negative, limbs))
+    }
+
+    //Parses unsigned digits in base 2 to 16
+    pub fn parse_radix(digits: &str, radix: u32) -> Option<Self> {
+        if digits.is_empty() {
+            return None;
+        }
+
+        let mut limbs = vec![];
+        for ch in digits.chars() {
+            mul_small_add(&mut limbs, radix, ch.to_digit(radix)?);
+        }
+
+        Some(BigInt::from_parts(false, limbs))
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    //Bits needed for the magnitude, 0 for zero
+    pub fn bits(&self) -> u64 {
+        match self.limbs.last() {
+            Some(top) => self.limbs.len() as u64 * 32 - top.leading_zeros() as u64,
+            None => 0,
+        }
+    }
+
+    //Not a From impl, a second one would break inference of BigInt::from(1)
+    pub fn from_i128(value: i128) -> Self {
+        let magnitude = value.unsigned_abs();
+        let limbs = (0..4).map(|i| (magnitude >> (32 * i)) as u32).collect();
+        BigInt::from_parts(value < 0, limbs)
+    }
+
+    pub fn to_i64(&self) -> Option<i64> {
+        self.to_i128().and_then(|value| i64::try_from(value).ok())
+    }
+
+    pub fn to_i128(&self) -> Option<i128> {
+        if self.limbs.len() > 4 {
+            return None;
+        }
+
+        let magnitude = self
+            .limbs
+            .iter()
+            .rev()
+            .fold(0u128, |acc, limb| (acc << 32) | *limb as u128);
+        if self.negative {
+            0i128.checked_sub_unsigned(magnitude)
+        } else {
+            i128::try_from(magnitude).ok()
+        }
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        let magnitude = self
+            .limbs
+            .iter()
+            .rev()
+            .fold(0f64, |acc, limb| acc * BASE as f64 + *limb as f64);
+        if self.negative {
+            -magnitude
+        } else {
+            magnitude
+        }
+    }
+
+    pub fn neg(&self) -> Self {
+        BigInt::from_parts(!self.negative, self.limbs.clone())
+    }
+
+    pub fn add(&self, other: &Self) -> Self {
+        if self.negative == other.negative {
+            return BigInt::from_parts(self.negative, add_magnitudes(&self.limbs, &other.limbs));
+        }
+
+        //Different signs, subtract the smaller magnitude from the larger one
+        match cmp_magnitudes(&self.limbs, &other.limbs) {
+            Ordering::Less => {
+                BigInt::from_parts(other.negative, sub_magnitudes(&other.limbs, &self.limbs))
+            }
+            _ => BigInt::from_parts(self.negative, sub_magnitudes(&self.limbs, &other.limbs)),
+        }
+    }
+
+    pub fn sub(&self, other: &Self) -> Self {
+        self.add(&other.neg())
+    }
+
+    pub fn mul(&self, other: &Self) -> Self {
+        let mut limbs = vec![0u32; self.limbs.len() + other.limbs.len()];
+
+        for (i, a) in self.limbs.iter().enumerate() {
+            let mut carry = 0u64;
+            for (j, b) in other.limbs.iter().enumerate() {
+                let current = limbs[i + j] as u64 + *a as u64 * *b as u64 + carry;
+                limbs[i + j] = current as u32;
+                carry = current >> 32;
+            }
+            limbs[i + other.limbs.len()] = carry as u32;
+        }
+
+        BigInt::from_parts(self.negative != other.negative, limbs)
+    }
+
+    //Truncating division like i64, the remainder has the sign of self. None
+    //if other is zero.
+    pub fn div_rem(&self, other: &Self) -> Option<(Self, Self)> {
+        if other.is_zero() {
+            return None;
+        }
+
+        let (quotient, remainder) = div_rem_magnitudes(&self.limbs, &other.limbs);
+        Some((
+            BigInt::from_parts(self.negative != other.negative, quotient),
+            BigInt::from_parts(self.negative, remainder),
+        ))
+    }
+
+    pub fn pow(&self, mut exponent: u32) -> Self {
+        let mut result = BigInt::from(1);
+        let mut base = self.clone();
+
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = result.mul(&base);
+            }
+            exponent >>= 1;
+            if exponent > 0 {
+                base = base.mul(&base);
+            }
+        }
+
+        result
+    }
+
+    //Bitwise operators act as if both values were in two's complement with
+    //infinite sign extension, which matches i64 for values that fit
+    pub fn and(&self, other: &Self) -> Self {
+        self.bitwise(other, |a, b| a & b)
+    }
+
+    pub fn or(&self, other: &Self) -> Self {
+        self.bitwise(other, |a, b| a | b)
+    }
+
+    pub fn xor(&self, other: &Self) -> Self {
+        self.bitwise(other, |a, b| a ^ b)
+    }
+
+    pub fn not(&self) -> Self {
+        self.neg().sub(&BigInt::from(1))
+    }
+
+    pub fn shl(&self, shift: u32) -> Self {
+        self.mul(&BigInt::from(2).pow(shift))
+    }
+
+    //Rounds towards negative infinity like an arithmetic shift on i64
+    pub fn shr(&self, shift: u32) -> Self {
+        let divisor = BigInt::from(2).pow(shift);
+        let (quotient, remainder) = self.div_rem(&divisor).expect("divisor is not zero");
+        if remainder.is_negative() {
+            quotient.sub(&BigInt::from(1))
+        } else {
+            quotient
+        }
+    }
+
+    fn bitwise(&self, other: &Self, operation: fn(u32, u32) -> u32) -> Self {
+        //One more limb than needed, so the top limb only holds the sign
+        let length = self.limbs.len().max(other.limbs.len()) + 1;
+        let left = self.twos_complement(length);
+        let right = other.twos_complement(length);
+
+        let limbs = left
+            .iter()
+            .zip(right.iter())
+            .map(|(a, b)| operation(*a, *b))
+            .collect();
+        BigInt::from_twos_complement(limbs)
+    }
+
+    fn twos_complement(&self, length: usize) -> Vec<u32> {
+        if !self.negative {
+            let mut limbs = self.limbs.clone();
+            limbs.resize(length, 0);
+            return limbs;
+        }
+
+        //-x is !(x - 1)
+        let mut limbs = sub_magnitudes(&self.limbs, &[1]);
+        limbs.resize(length, 0);
+        limbs.iter_mut().for_each(|limb| *limb = !*limb);
+        limbs
+    }
+
+    fn from_twos_complement(mut limbs: Vec<u32>) -> Self {
+        let negative = limbs.last().is_some_and(|limb| limb >> 31 == 1);
+        if !negative {
+            return BigInt::from_parts(false, limbs);
+        }
+
+        limbs.iter_mut().for_each(|limb| *limb = !*limb);
+        BigInt::from_parts(true, add_magnitudes(&limbs, &[1]))
+    }
+
+    fn from_parts(negative: bool, mut limbs: Vec<u32>) -> Self {
+        while limbs.last() == Some(&0) {
+            limbs.pop();
+        }
+        BigInt {
+            negative: negative && !limbs.is_empty(),
+            limbs,
+        }
+    }
+}
+
+impl From<i64> for BigInt {
+    fn from(value: i64) -> Self {
+        let magnitude = value.unsigned_abs();
+        BigInt::from_parts(value < 0, vec![magnitude as u32, (magnitude >> 32) as u32])
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => cmp_magnitudes(&self.limbs, &other.limbs),
+            (true, true) => cmp_magnitudes(&other.limbs, &self.limbs),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Display for BigInt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+
+        //Split off 9 decimal digits at a time, least significant first
+        let mut chunks = vec![];
+        let mut limbs = self.limbs.clone();
+        while !limbs.is_empty() {
+            chunks.push(div_small(&mut limbs, DECIMAL_BASE));
+        }
+
+        if self.negative {
+            write!(f, "-")?;
+        }
+        let mut chunks = chunks.iter().rev();
+        if let Some(first) = chunks.next() {
+            write!(f, "{}", first)?;
+        }
+        for chunk in chunks {
+            write!(f, "{:0width$}", chunk, width = DECIMAL_DIGITS)?;
+        }
+
+        Ok(())
+    }
+}
+
+//helper
+fn cmp_magnitudes(a: &[u32], b: &[u32]) -> Ordering {
+    a.len()
+        .cmp(&b.len())
+        .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_magnitudes(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut limbs = Vec::with_capacity(a.len().max(b.len()) + 1);
+    let mut carry = 0u64;
+
+    for i in 0..a.len().max(b.len()) {
+        let sum = *a.get(i).unwrap_or(&0) as u64 + *b.get(i).unwrap_or(&0) as u64 + carry;
+        limbs.push(sum as u32);
+        carry = sum >> 32;
+    }
+    limbs.push(carry as u32);
+
+    limbs
+}
+
+//Requires a >= b
+fn sub_magnitudes(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut limbs = Vec::with_capacity(a.len());
+    let mut borrow = 0i64;
+
+    for (i, limb) in a.iter().enumerate() {
+        let mut difference = *limb as i64 - *b.get(i).unwrap_or(&0) as i64 - borrow;
+        borrow = 0;
+        if difference < 0 {
+            difference += BASE as i64;
+            borrow = 1;
+        }
+        limbs.push(difference as u32);
+    }
+
+    limbs
+}
+
+fn mul_small_add(limbs: &mut Vec<u32>, multiplier: u32, addend: u32) {
+    let mut carry = addend as u64;
+    for limb in limbs.iter_mut() {
+        let current = *limb as u64 * multiplier as u64 + carry;
+        *limb = current as u32;
+        carry = current >> 32;
+    }
+    if carry > 0 {
+        limbs.push(carry as u32);
+    }
+}
+
+//Divides in place and returns the remainder
+fn div_small(limbs: &mut Vec<u32>, divisor: u32) -> u32 {
+    let mut remainder = 0u64;
+    for limb in limbs.iter_mut().rev() {
+        let current = (remainder << 32) | *limb as u64;
+        *limb = (current / divisor as u64) as u32;
+        remainder = current % divisor as u64;
+    }
+    while limbs.last() == Some(&0) {
+        limbs.pop();
+    }
+
+    remainder as u32
+}
+
+//Schoolbook binary long division, one bit of the dividend at a time
+fn div_rem_magnitudes(dividend: &[u32], divisor: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    if cmp_magnitudes(dividend, divisor) == Ordering::Less {
+        return (vec![], dividend.to_vec());
+    }
+    if divisor.len() == 1 {
+        let mut quotient = dividend.to_vec();
+        let remainder = div_small(&mut quotient, divisor[0]);
+        return (quotient, vec![remainder]);
+    }
+
+    let mut quotient = vec![0u32; dividend.len()];
+    let mut remainder: Vec<u32> = vec![];
+
+    for bit in (0..dividend.len() * 32).rev() {
+        shift_left_one(&mut remainder, (dividend[bit / 32] >> (bit % 32)) & 1);
+        if cmp_magnitudes(&remainder, divisor) != Ordering::Less {
+            remainder = sub_magnitudes(&remainder, divisor);
+            while remainder.last() == Some(&0) {
+                remainder.pop();
+            }
+            quotient[bit / 32] |= 1 << (bit % 32);
+        }
+    }
+
+    (quotient, remainder)
+}
+
+fn shift_left_one(limbs: &mut Vec<u32>, low_bit: u32) {
+    let mut carry = low_bit;
+    for limb in limbs.iter_mut() {
+        let next_carry = *limb >> 31;
+        *limb = (*limb << 1) | carry;
+        carry = next_carry;
+    }
+    if carry > 0 {
+        limbs.push(carry);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_display_test() {
+        let test_inputs = [
+            "0",
+            "7",
+            "-7",
+            "4294967296",
+            "18446744073709551616",
+            "-123456789012345678901234567890",
+            "1000000000000000000000000000000000000001",
+        ];
+
+        for input in test_inputs.iter() {
+            assert_eq!(*input, BigInt::parse(input).unwrap().to_string());
+        }
+
+        assert_eq!("0", BigInt::parse("-0").unwrap().to_string());
+        assert_eq!("42", BigInt::parse("00042").unwrap().to_string());
+        assert_eq!(None, BigInt::parse("12a"));
+        assert_eq!(
+            "340282366920938463463374607431768211455",
+            BigInt::parse_radix("ffffffffffffffffffffffffffffffff", 16)
+                .unwrap()
+                .to_string()
+        );
+        assert_eq!("5", BigInt::parse_radix("101", 2).unwrap().to_string());
+        assert_eq!(None, BigInt::parse_radix("102", 2));
+        assert_eq!(None, BigInt::parse("-"));
+    }
+
+    #[test]
+    fn i64_conversion_test() {
+        let test_inputs = [0, 1, -1, i64::MAX, i64::MIN, 1 << 32, -(1 << 40)];
+
+        for input in test_inputs.iter() {
+            let big = BigInt::from(*input);
+            assert_eq!(input.to_string(), big.to_string());
+            assert_eq!(Some(*input), big.to_i64());
+        }
+
+        assert_eq!(None, BigInt::parse("9223372036854775808").unwrap().to_i64());
+        for input in [i128::MAX, i128::MIN, 1 << 100, -(1 << 64)] {
+            let big = BigInt::from_i128(input);
+            assert_eq!(input.to_string(), big.to_string());
+            assert_eq!(Some(input), big.to_i128());
+        }
+        assert_eq!(
+            None,
+            BigInt::from_i128(i128::MIN).sub(&BigInt::from(1)).to_i128()
+        );
+        assert_eq!(
+            None,
+            BigInt::parse("-9223372036854775809").unwrap().to_i64()
+        );
+    }
+
+    #[test]
+    fn bits_test() {
+        let test_inputs = [
+            ("0", 0),
+            ("1", 1),
+            ("-1", 1),
+            ("255", 8),
+            ("4294967295", 32),
+            ("4294967296", 33),
+            ("-18446744073709551616", 65),
+        ];
+
+        for input in test_inputs.iter() {
+            assert_eq!(
+                input.1,
+                BigInt::parse(input.0).unwrap().bits(),
+                "{}",
+                input.0
+            );
+        }
+    }
+
+    #[test]
+    fn arithmetic_test() {
+        let big = |text: &str| BigInt::parse(text).unwrap();
+        let max = BigInt::from(i64::MAX);
+
+        assert_eq!(big("9223372036854775808"), max.add(&BigInt::from(1)));
+        assert_eq!(
+            big("-9223372036854775809"),
+            BigInt::from(i64::MIN).sub(&BigInt::from(1))
+        );
+        assert_eq!(BigInt::zero(), max.sub(&max));
+        assert_eq!(BigInt::from(-5), BigInt::from(3).add(&BigInt::from(-8)));
+        assert_eq!(big("85070591730234615847396907784232501249"), max.mul(&max));
+        assert_eq!(
+            big("-85070591730234615847396907784232501249"),
+            max.mul(&max.neg())
+        );
+        assert_eq!(
+            big("1267650600228229401496703205376"),
+            BigInt::from(2).pow(100)
+        );
+        assert_eq!(BigInt::from(1), BigInt::from(12345).pow(0));
+    }
+
+    #[test]
+    fn bitwise_test() {
+        let big = |text: &str| BigInt::parse(text).unwrap();
+        let values = [
+            0,
+            1,
+            -1,
+            6,
+            -6,
+            255,
+            -256,
+            i64::MAX,
+            i64::MIN,
+            0x0F0F_0F0F_0F0F,
+        ];
+
+        //Has to agree with i64 wherever the result fits
+        for a in values {
+            for b in values {
+                let (big_a, big_b) = (BigInt::from(a), BigInt::from(b));
+                assert_eq!(BigInt::from(a & b), big_a.and(&big_b), "{} & {}", a, b);
+                assert_eq!(BigInt::from(a | b), big_a.or(&big_b), "{} | {}", a, b);
+                assert_eq!(BigInt::from(a ^ b), big_a.xor(&big_b), "{} ^ {}", a, b);
+            }
+            assert_eq!(BigInt::from(!a), BigInt::from(a).not(), "~{}", a);
+            assert_eq!(BigInt::from(a >> 3), BigInt::from(a).shr(3), "{} >> 3", a);
+        }
+
+        let huge = big("-340282366920938463463374607431768211456");
+        assert_eq!(huge, huge.and(&BigInt::from(-1)));
+        assert_eq!(BigInt::zero(), huge.and(&BigInt::from(i64::MAX)));
+        assert_eq!(
+            big("-1267650600228229401496703205376"),
+            BigInt::from(-1).shl(100)
+        );
+        assert_eq!(BigInt::from(-1), BigInt::from(-1).shr(100));
+    }
+
+    #[test]
+    fn div_rem_test() {
+        let big = |text: &str| BigInt::parse(text).unwrap();
+
+        let test_inputs = [
+            ("100", "7", "14", "2"),
+            ("-100", "7", "-14", "-2"),
+            ("100", "-7", "-14", "2"),
+            (
+                "85070591730234615847396907784232501249",
+                "9223372036854775807",
+                "9223372036854775807",
+                "0",
+            ),
+            (
+                "123456789012345678901234567890",
+                "98765432109876543210",
+                "1249999988",
+                "60185185207253086410",
+            ),
+            ("5", "18446744073709551616", "0", "5"),
+        ];
+
+        for input in test_inputs.iter() {
+            let (quotient, remainder) = big(input.0).div_rem(&big(input.1)).unwrap();
+            assert_eq!(
+                (big(input.2), big(input.3)),
+                (quotient, remainder),
+                "{} / {}",
+                input.0,
+                input.1
+            );
+        }
+
+        assert_eq!(None, BigInt::from(1).div_rem(&BigInt::zero()));
+    }
+
+    #[test]
+    fn ordering_test() {
+        let mut values = [
+            BigInt::parse("18446744073709551616").unwrap(),
+            BigInt::from(-1),
+            BigInt::parse("-18446744073709551616").unwrap(),
+            BigInt::zero(),
+            BigInt::from(i64::MAX),
+        ];
+        values.sort();
+
+        let sorted: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+        assert_eq!(
+            vec![
+                "-18446744073709551616",
+                "-1",
+                "0",
+                "9223372036854775807",
+                "18446744073709551616"
+            ],
+            sorted
+        );
+    }
+}
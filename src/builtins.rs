@@ -0,0 +1,137 @@
+use std::{fmt::Debug, rc::Rc};
+
+#[cfg(feature = "bignum")]
+use crate::bignum::BigInt;
+use crate::object::Object;
+
+pub type BuiltinFunction = Rc<dyn Fn(Vec<Object>) -> Object>;
+type NativeFunction = fn(Vec<Object>) -> Object;
+
+//A function implemented in Rust, either one of the functions below or one
+//registered by the host through Interpreter::register_fn
+#[derive(Clone)]
+pub struct Builtin {
+    pub name: Rc<str>,
+    pub function: BuiltinFunction,
+}
+
+impl Builtin {
+    pub fn new(name: &str, function: impl Fn(Vec<Object>) -> Object + 'static) -> Self {
+        Builtin {
+            name: Rc::from(name),
+            function: Rc::new(function),
+        }
+    }
+
+    pub fn call(&self, arguments: Vec<Object>) -> Object {
+        (self.function)(arguments)
+    }
+}
+
+//Closures cannot be compared, the name is
+impl PartialEq for Builtin {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+impl Debug for Builtin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Builtin").field("name", &self.name).finish()
+    }
+}
+
+const BUILTINS: &[(&str, NativeFunction)] = &[("int", int), ("float", float), ("str", str)];
+
+//Builtins are looked up after the environment, so a binding with the same
+//name shadows them
+pub fn get_builtin(name: &str) -> Option<Object> {
+    BUILTINS
+        .iter()
+        .find(|(builtin, _)| *builtin == name)
+        .map(|(name, function)| Object::Builtin(Builtin::new(name, *function)))
+}
+
+//Truncates floats towards zero and parses strings
+fn int(arguments: Vec<Object>) -> Object {
+    let argument = match single_argument(arguments) {
+        Ok(argument) => argument,
+        Err(error) => return error,
+    };
+
+    match argument {
+        Object::Integer(_) => argument,
+        #[cfg(feature = "bignum")]
+        Object::BigInteger(_) => argument,
+        Object::Float(value) => {
+            //The bounds are exact as floats, i64::MAX itself is not
+            if value.is_finite() && value >= -(2f64.powi(63)) && value < 2f64.powi(63) {
+                Object::Integer(value as i64)
+            } else {
+                Object::Error(format!("cannot convert {:?} to INTEGER", value))
+            }
+        }
+        Object::Boolean(value) => Object::Integer(value as i64),
+        Object::String(value) => match parse_integer(value.trim()) {
+            Some(integer) => integer,
+            None => Object::Error(format!("cannot convert {:?} to INTEGER", value)),
+        },
+        _ => unsupported_argument("int", &argument),
+    }
+}
+
+fn float(arguments: Vec<Object>) -> Object {
+    let argument = match single_argument(arguments) {
+        Ok(argument) => argument,
+        Err(error) => return error,
+    };
+
+    match argument {
+        Object::Integer(value) => Object::Float(value as f64),
+        #[cfg(feature = "bignum")]
+        Object::BigInteger(value) => Object::Float(value.to_f64()),
+        Object::Float(_) => argument,
+        Object::String(value) => match value.trim().parse() {
+            Ok(value) => Object::Float(value),
+            Err(_) => Object::Error(format!("cannot convert {:?} to FLOAT", value)),
+        },
+        _ => unsupported_argument("float", &argument),
+    }
+}
+
+fn str(arguments: Vec<Object>) -> Object {
+    match single_argument(arguments) {
+        Ok(argument) => Object::String(argument.to_string()),
+        Err(error) => error,
+    }
+}
+
+//helper
+fn parse_integer(text: &str) -> Option<Object> {
+    match text.parse() {
+        Ok(value) => Some(Object::Integer(value)),
+        #[cfg(feature = "bignum")]
+        Err(_) => BigInt::parse(text).map(Object::from),
+        #[cfg(not(feature = "bignum"))]
+        Err(_) => None,
+    }
+}
+
+fn single_argument(arguments: Vec<Object>) -> Result<Object, Object> {
+    if arguments.len() != 1 {
+        return Err(Object::Error(format!(
+            "wrong number of arguments: want=1, got={}",
+            arguments.len()
+        )));
+    }
+
+    Ok(arguments.into_iter().next().unwrap_or(Object::Null))
+}
+
+fn unsupported_argument(name: &str, argument: &Object) -> Object {
+    Object::Error(format!(
+        "argument to `{}` not supported, got {}",
+        name,
+        argument.type_name()
+    ))
+}
@@ -0,0 +1,293 @@
+use std::fmt::{Display, Write};
+
+use crate::span::Span;
+
+const RED: &str = "\x1b[1;31m";
+const YELLOW: &str = "\x1b[1;33m";
+const BLUE: &str = "\x1b[1;34m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+}
+
+impl Severity {
+    fn color(&self) -> &'static str {
+        match self {
+            Severity::Error => RED,
+            Severity::Warning => YELLOW,
+            Severity::Note => BLUE,
+        }
+    }
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Note => write!(f, "note"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Label {
+    pub span: Span,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: Option<String>,
+    pub message: String,
+    pub primary: Label,
+    pub secondary: Vec<Label>,
+    pub notes: Vec<String>,
+    pub help: Option<String>,
+}
+
+impl Diagnostic {
+    pub fn new(severity: Severity, message: &str, span: Span) -> Self {
+        Diagnostic {
+            severity,
+            code: None,
+            message: message.to_string(),
+            primary: Label {
+                span,
+                message: String::new(),
+            },
+            secondary: vec![],
+            notes: vec![],
+            help: None,
+        }
+    }
+
+    pub fn error(message: &str, span: Span) -> Self {
+        Diagnostic::new(Severity::Error, message, span)
+    }
+
+    pub fn with_code(mut self, code: &str) -> Self {
+        self.code = Some(code.to_string());
+        self
+    }
+
+    //Message printed next to the carets under the primary span
+    pub fn with_label(mut self, message: &str) -> Self {
+        self.primary.message = message.to_string();
+        self
+    }
+
+    pub fn with_secondary(mut self, span: Span, message: &str) -> Self {
+        self.secondary.push(Label {
+            span,
+            message: message.to_string(),
+        });
+        self
+    }
+
+    pub fn with_note(mut self, note: &str) -> Self {
+        self.notes.push(note.to_string());
+        self
+    }
+
+    pub fn with_help(mut self, help: &str) -> Self {
+        self.help = Some(help.to_string());
+        self
+    }
+
+    pub fn span(&self) -> Span {
+        self.primary.span
+    }
+
+    //Renders the diagnostic in the style of
+    //
+    //  error[E0001]: Expected Assign, found 5
+    //   --> 1:7
+    //    |
+    //  1 | let x 5;
+    //    |       ^ expected `=`
+    //
+    //Every label gets its source line printed with the span underlined, `^` for
+    //the primary and `-` for secondary labels.
+    pub fn render(&self, source: &str, color: bool) -> String {
+        let paint = |style: &'static str| if color { style } else { "" };
+        let reset = paint(RESET);
+
+        let mut labels: Vec<(&Label, bool)> = vec![(&self.primary, true)];
+        labels.extend(self.secondary.iter().map(|label| (label, false)));
+        labels.sort_by_key(|(label, _)| (label.span.line, label.span.column));
+
+        let gutter_width = labels
+            .iter()
+            .map(|(label, _)| label.span.line.to_string().len())
+            .max()
+            .unwrap_or(1);
+        let gutter = " ".repeat(gutter_width);
+
+        let mut out = String::new();
+        let _ = write!(out, "{}{}", paint(self.severity.color()), self.severity);
+        if let Some(code) = &self.code {
+            let _ = write!(out, "[{}]", code);
+        }
+        let _ = writeln!(out, "{}{}: {}{}", reset, paint(BOLD), self.message, reset);
+        let _ = writeln!(
+            out,
+            "{}{}-->{} {}",
+            gutter,
+            paint(BLUE),
+            reset,
+            self.primary.span
+        );
+        let _ = writeln!(out, "{} {}|{}", gutter, paint(BLUE), reset);
+
+        let lines: Vec<&str> = source.lines().collect();
+        let mut last_line = 0;
+        for (label, primary) in labels {
+            let line_number = label.span.line;
+            let line = lines
+                .get(line_number.saturating_sub(1))
+                .copied()
+                .unwrap_or("");
+
+            if line_number != last_line {
+                let _ = writeln!(
+                    out,
+                    "{}{:>width$} |{} {}",
+                    paint(BLUE),
+                    line_number,
+                    reset,
+                    line,
+                    width = gutter_width
+                );
+                last_line = line_number;
+            }
+
+            let (marker, style) = if primary {
+                ('^', paint(self.severity.color()))
+            } else {
+                ('-', paint(BLUE))
+            };
+            let column = label.span.column.max(1);
+            let underline_len = underline_len(source, line, column, label.span);
+
+            let _ = write!(
+                out,
+                "{} {}|{} {}{}{}",
+                gutter,
+                paint(BLUE),
+                reset,
+                " ".repeat(column - 1),
+                style,
+                marker.to_string().repeat(underline_len)
+            );
+            if !label.message.is_empty() {
+                let _ = write!(out, " {}", label.message);
+            }
+            let _ = writeln!(out, "{}", reset);
+        }
+
+        for note in self.notes.iter() {
+            let _ = writeln!(out, "{} {}={} note: {}", gutter, paint(BLUE), reset, note);
+        }
+        if let Some(help) = &self.help {
+            let _ = writeln!(out, "{} {}={} help: {}", gutter, paint(BLUE), reset, help);
+        }
+
+        out
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.severity)?;
+        if let Some(code) = &self.code {
+            write!(f, "[{}]", code)?;
+        }
+        write!(f, ": {} at {}", self.message, self.primary.span)
+    }
+}
+
+//Number of characters to underline: the span clipped to the end of its first
+//line, but always at least one so empty spans (e.g. at Eof) stay visible.
+//Spans are in bytes but columns in characters, so the characters are counted.
+fn underline_len(source: &str, line: &str, column: usize, span: Span) -> usize {
+    let remaining = line.chars().count().saturating_sub(column - 1);
+    let span_len = source
+        .get(span.start..span.end)
+        .map_or(span.len(), |text| text.chars().count())
+        .min(remaining);
+
+    span_len.max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_primary_label_test() {
+        let source = "let x = 5;\nlet y 10;";
+        let diagnostic = Diagnostic::error("Expected Assign, found 10", Span::new(17, 19, 2, 7))
+            .with_code("E0001")
+            .with_label("expected `=`")
+            .with_help("insert `=` after the name");
+
+        let expected = "error[E0001]: Expected Assign, found 10
+ --> 2:7
+  |
+2 | let y 10;
+  |       ^^ expected `=`
+  = help: insert `=` after the name
+";
+        assert_eq!(expected, diagnostic.render(source, false));
+    }
+
+    #[test]
+    fn render_secondary_labels_and_notes_test() {
+        let source = "let add = fn(x, y) {\n  x + z\n};";
+        let diagnostic = Diagnostic::error("identifier not found: z", Span::new(27, 28, 2, 7))
+            .with_secondary(Span::new(10, 18, 1, 11), "in this function")
+            .with_note("parameters are x, y");
+
+        let expected = "error: identifier not found: z
+ --> 2:7
+  |
+1 | let add = fn(x, y) {
+  |           -------- in this function
+2 |   x + z
+  |       ^
+  = note: parameters are x, y
+";
+        assert_eq!(expected, diagnostic.render(source, false));
+    }
+
+    #[test]
+    fn render_non_ascii_test() {
+        let source = "let s = \"é\" - 1;";
+        let diagnostic =
+            Diagnostic::error("unknown operator: STRING - INTEGER", Span::new(8, 16, 1, 9));
+
+        let expected = "error: unknown operator: STRING - INTEGER
+ --> 1:9
+  |
+1 | let s = \"é\" - 1;
+  |         ^^^^^^^
+";
+        assert_eq!(expected, diagnostic.render(source, false));
+    }
+
+    #[test]
+    fn render_with_color_test() {
+        let diagnostic = Diagnostic::error("boom", Span::new(0, 1, 1, 1));
+        let rendered = diagnostic.render("x", true);
+
+        assert!(rendered.starts_with("\x1b[1;31merror"));
+        assert!(rendered.contains("\x1b[1;31m^"));
+    }
+}
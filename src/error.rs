@@ -1,2 +1,27 @@
-#[derive(Debug)]
-pub struct ParsingError(pub String);
+use std::fmt::Display;
+
+use crate::diagnostic::Diagnostic;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsingError(pub Box<Diagnostic>);
+
+impl ParsingError {
+    pub fn diagnostic(&self) -> &Diagnostic {
+        &self.0
+    }
+
+    //Same as Diagnostic::render, source has to be the parsed input
+    pub fn render(&self, source: &str, color: bool) -> String {
+        self.0.render(source, color)
+    }
+}
+
+//Only the message, render shows the code and the source around the span
+impl Display for ParsingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let span = self.0.span();
+        write!(f, "{}:{}: {}", span.line, span.column, self.0.message)
+    }
+}
+
+impl std::error::Error for ParsingError {}
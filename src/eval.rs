@@ -0,0 +1,1614 @@
+use std::{cell::RefCell, fmt::Display, rc::Rc};
+
+#[cfg(feature = "bignum")]
+use crate::bignum::BigInt;
+use crate::{
+    ast::Programm,
+    builtins::get_builtin,
+    environment::{Env, Environment},
+    limits::{Budget, Limits},
+    object::{Function, HashObject, Object},
+    statements::{BlockStatement, Expressions, FunctionCache, Identifier, Statements},
+};
+
+//Evaluates with the default limits, see Limits
+pub fn eval_programm(programm: &Programm, env: &Env) -> Object {
+    eval_programm_with_budget(programm, env, &Budget::new(Limits::default()))
+}
+
+pub fn eval_programm_with_budget(programm: &Programm, env: &Env, budget: &Budget) -> Object {
+    let mut result = Object::Null;
+
+    for statement in programm.statements.iter() {
+        result = eval_statement(statement, env, budget);
+
+        match result {
+            Object::ReturnValue(value) => return *value,
+            Object::Error(_) => return result,
+            _ => {}
+        }
+    }
+
+    result
+}
+
+//Statements
+fn eval_statement(statement: &Statements, env: &Env, budget: &Budget) -> Object {
+    match statement {
+        Statements::Let(_, identifier, expression) => {
+            let value = eval_expression(expression, env, budget);
+            if value.is_error() {
+                return value;
+            }
+
+            env.borrow_mut().set(&identifier.value, value);
+            Object::Null
+        }
+        Statements::Return(_, expression) => {
+            let value = eval_expression(expression, env, budget);
+            if value.is_error() {
+                return value;
+            }
+
+            Object::ReturnValue(Box::new(value))
+        }
+        Statements::Expression(_, expression) => eval_expression(expression, env, budget),
+        Statements::Error(span) => Object::Error(format!("invalid statement at {}", span)),
+    }
+}
+
+//Unlike eval_programm, a ReturnValue is passed on as is so that it unwinds
+//through every enclosing block up to the function call or the programm.
+fn eval_block_statement(block: &BlockStatement, env: &Env, budget: &Budget) -> Object {
+    let mut result = Object::Null;
+
+    for statement in block.statements.iter() {
+        result = eval_statement(statement, env, budget);
+
+        if matches!(result, Object::ReturnValue(_) | Object::Error(_)) {
+            return result;
+        }
+    }
+
+    result
+}
+
+//Expressions
+//Every nested expression and call goes through here, so this is where the
+//depth of the recursion is limited
+fn eval_expression(expression: &Expressions, env: &Env, budget: &Budget) -> Object {
+    if let Err(error) = budget.step().and_then(|_| budget.enter_expression()) {
+        return error;
+    }
+    let result = eval_nested_expression(expression, env, budget);
+    budget.exit_expression();
+
+    result
+}
+
+//Only dispatches, the work of each arm is done in its own function. Keeping
+//the stack frames small is what allows deep recursion within the limits.
+fn eval_nested_expression(expression: &Expressions, env: &Env, budget: &Budget) -> Object {
+    match expression {
+        Expressions::IntegerLiteral(_, value) => Object::Integer(*value),
+        #[cfg(feature = "bignum")]
+        Expressions::BigIntegerLiteral(_, value) => {
+            budget.check_allocation(Object::BigInteger(value.clone()))
+        }
+        Expressions::FloatLiteral(_, value) => Object::Float(*value),
+        Expressions::StringLiteral(_, value) => Object::String(value.to_string()),
+        Expressions::BooleanExpression(_, value) => Object::Boolean(*value),
+        Expressions::PrefixExpression(_, operator, right) => {
+            eval_prefix(operator, right, env, budget)
+        }
+        Expressions::InfixExpression(_, left, operator, right) => {
+            eval_infix(left, operator, right, env, budget)
+        }
+        Expressions::Identifier(identifier) => eval_identifier(identifier, env),
+        Expressions::FunctionLiteral(_, parameters, body, cache) => {
+            eval_function_literal(parameters, body, cache, env)
+        }
+        Expressions::CallExpression(_, function, arguments, _) => {
+            eval_call(function, arguments, env, budget)
+        }
+        Expressions::AssignExpression(_, name, operator, value) => {
+            eval_assign(name, operator, value, env, budget)
+        }
+        Expressions::IfExpression(_, condition, consequence, alternative) => {
+            eval_if(condition, consequence, alternative.as_ref(), env, budget)
+        }
+        Expressions::ArrayLiteral(_, elements, _) => eval_array_literal(elements, env, budget),
+        Expressions::HashLiteral(_, pairs, _) => eval_hash_literal(pairs, env, budget),
+        Expressions::IndexExpression(_, left, index, _) => eval_index(left, index, env, budget),
+        Expressions::SliceExpression(_, left, start, end, _) => {
+            eval_slice(left, start.as_deref(), end.as_deref(), env, budget)
+        }
+    }
+}
+
+fn eval_prefix(operator: &str, right: &Expressions, env: &Env, budget: &Budget) -> Object {
+    let right = eval_expression(right, env, budget);
+    if right.is_error() {
+        return right;
+    }
+
+    eval_prefix_expression(operator, right)
+}
+
+//A chain like `1 + 2 + 3` nests to the left, as deep as it is long. Its links
+//are evaluated in a loop, so only the first operand is a level of recursion.
+fn eval_infix(
+    left: &Expressions,
+    operator: &str,
+    right: &Expressions,
+    env: &Env,
+    budget: &Budget,
+) -> Object {
+    let mut links = vec![(operator, right)];
+    let mut first = left;
+    while let Expressions::InfixExpression(_, left, operator, right) = first {
+        //Each link is still a step of its own
+        if let Err(error) = budget.step() {
+            return error;
+        }
+
+        links.push((operator, right));
+        first = left;
+    }
+
+    let mut value = eval_expression(first, env, budget);
+    for (operator, right) in links.into_iter().rev() {
+        if value.is_error() {
+            return value;
+        }
+
+        value = eval_infix_link(value, operator, right, env, budget);
+    }
+
+    value
+}
+
+fn eval_infix_link(
+    left: Object,
+    operator: &str,
+    right: &Expressions,
+    env: &Env,
+    budget: &Budget,
+) -> Object {
+    //The right side is only evaluated if it decides the result
+    match (operator, is_truthy(&left)) {
+        ("&&", false) => return Object::Boolean(false),
+        ("||", true) => return Object::Boolean(true),
+        _ => {}
+    }
+
+    let right = eval_expression(right, env, budget);
+    if right.is_error() {
+        return right;
+    }
+
+    #[cfg(feature = "bignum")]
+    if let Err(error) = budget.reserve_integer(power_size(operator, &left, &right)) {
+        return error;
+    }
+
+    budget.check_allocation(eval_infix_expression(operator, left, right))
+}
+
+//A power can take far more memory than its operands, so a lower bound of its
+//size in bytes is checked before computing it. 0 for everything else.
+#[cfg(feature = "bignum")]
+fn power_size(operator: &str, left: &Object, right: &Object) -> usize {
+    let bits = match left {
+        Object::Integer(value) => (64 - value.unsigned_abs().leading_zeros()) as u64,
+        Object::BigInteger(value) => value.bits(),
+        _ => return 0,
+    };
+
+    match (operator, right) {
+        //The result has at least (bits - 1) * exponent + 1 bits
+        ("**", Object::Integer(exponent)) if bits > 1 && *exponent > 0 => {
+            let bits = (bits - 1)
+                .saturating_mul(*exponent as u64)
+                .saturating_add(1);
+            usize::try_from(bits.div_ceil(8)).unwrap_or(usize::MAX)
+        }
+        _ => 0,
+    }
+}
+
+fn eval_identifier(identifier: &Identifier, env: &Env) -> Object {
+    match env.borrow().get(&identifier.value) {
+        Some(value) => value,
+        None => get_builtin(&identifier.value).unwrap_or_else(|| {
+            Object::Error(format!("identifier not found: {}", identifier.value))
+        }),
+    }
+}
+
+//The owned copy is made once per literal, later evaluations only change
+//reference counts
+fn eval_function_literal(
+    parameters: &[Identifier],
+    body: &BlockStatement,
+    cache: &FunctionCache,
+    env: &Env,
+) -> Object {
+    let (parameters, body) = cache.get_or_init(parameters, body);
+
+    Object::Function(Function {
+        parameters: Rc::clone(parameters),
+        body: Rc::clone(body),
+        env: Rc::clone(env),
+    })
+}
+
+fn eval_call(
+    function: &Expressions,
+    arguments: &[Expressions],
+    env: &Env,
+    budget: &Budget,
+) -> Object {
+    let function = eval_expression(function, env, budget);
+    if function.is_error() {
+        return function;
+    }
+
+    match eval_expressions(arguments, env, budget) {
+        Ok(arguments) => apply_function(function, arguments, budget),
+        Err(error) => error,
+    }
+}
+
+fn eval_assign(
+    name: &Identifier,
+    operator: &str,
+    value: &Expressions,
+    env: &Env,
+    budget: &Budget,
+) -> Object {
+    let mut value = eval_expression(value, env, budget);
+    if value.is_error() {
+        return value;
+    }
+
+    //Compound assignments apply the operator in front of the `=`
+    if let Some(operator) = operator.strip_suffix('=').filter(|op| !op.is_empty()) {
+        let current = match env.borrow().get(&name.value) {
+            Some(current) => current,
+            None => return Object::Error(format!("identifier not found: {}", name.value)),
+        };
+
+        value = budget.check_allocation(eval_infix_expression(operator, current, value));
+        if value.is_error() {
+            return value;
+        }
+    }
+
+    if !env.borrow_mut().assign(&name.value, value.clone()) {
+        return Object::Error(format!("identifier not found: {}", name.value));
+    }
+
+    value
+}
+
+fn eval_if(
+    condition: &Expressions,
+    consequence: &BlockStatement,
+    alternative: Option<&BlockStatement>,
+    env: &Env,
+    budget: &Budget,
+) -> Object {
+    let condition = eval_expression(condition, env, budget);
+    if condition.is_error() {
+        return condition;
+    }
+
+    if is_truthy(&condition) {
+        eval_block_statement(consequence, env, budget)
+    } else if let Some(alternative) = alternative {
+        eval_block_statement(alternative, env, budget)
+    } else {
+        Object::Null
+    }
+}
+
+fn eval_array_literal(elements: &[Expressions], env: &Env, budget: &Budget) -> Object {
+    match eval_expressions(elements, env, budget) {
+        Ok(elements) => budget.check_allocation(Object::Array(elements)),
+        Err(error) => error,
+    }
+}
+
+fn eval_hash_literal(pairs: &[(Expressions, Expressions)], env: &Env, budget: &Budget) -> Object {
+    let mut hash = HashObject::new();
+
+    for (key, value) in pairs.iter() {
+        let key = eval_expression(key, env, budget);
+        if key.is_error() {
+            return key;
+        }
+
+        let value = eval_expression(value, env, budget);
+        if value.is_error() {
+            return value;
+        }
+
+        if let Err(key) = hash.insert(key, value) {
+            return Object::Error(format!("unusable as hash key: {}", key.type_name()));
+        }
+    }
+
+    budget.check_allocation(Object::Hash(hash))
+}
+
+fn eval_index(left: &Expressions, index: &Expressions, env: &Env, budget: &Budget) -> Object {
+    let left = eval_expression(left, env, budget);
+    if left.is_error() {
+        return left;
+    }
+
+    let index = eval_expression(index, env, budget);
+    if index.is_error() {
+        return index;
+    }
+
+    eval_index_expression(left, index)
+}
+
+fn eval_slice(
+    left: &Expressions,
+    start: Option<&Expressions>,
+    end: Option<&Expressions>,
+    env: &Env,
+    budget: &Budget,
+) -> Object {
+    let left = eval_expression(left, env, budget);
+    if left.is_error() {
+        return left;
+    }
+
+    let mut bounds = [None, None];
+    for (bound, expression) in bounds.iter_mut().zip([start, end]) {
+        if let Some(expression) = expression {
+            let value = eval_expression(expression, env, budget);
+            if value.is_error() {
+                return value;
+            }
+            *bound = Some(value);
+        }
+    }
+    let [start, end] = bounds;
+
+    eval_slice_expression(left, start, end)
+}
+
+//Evaluates expressions left to right, stopping at the first error
+fn eval_expressions(
+    expressions: &[Expressions],
+    env: &Env,
+    budget: &Budget,
+) -> Result<Vec<Object>, Object> {
+    let mut values = vec![];
+
+    for expression in expressions.iter() {
+        let value = eval_expression(expression, env, budget);
+        if value.is_error() {
+            return Err(value);
+        }
+        values.push(value);
+    }
+
+    Ok(values)
+}
+
+fn eval_index_expression(left: Object, index: Object) -> Object {
+    match (&left, &index) {
+        (Object::Array(elements), Object::Integer(index)) => {
+            match resolve_index(*index, elements.len()) {
+                Some(position) if position < elements.len() => elements[position].clone(),
+                _ => Object::Error(format!(
+                    "index out of bounds: index {}, length {}",
+                    index,
+                    elements.len()
+                )),
+            }
+        }
+        #[cfg(feature = "bignum")]
+        (Object::Array(elements), Object::BigInteger(index)) => Object::Error(format!(
+            "index out of bounds: index {}, length {}",
+            index,
+            elements.len()
+        )),
+        (Object::Array(_), _) => Object::Error(format!(
+            "index must be an INTEGER, got {}",
+            index.type_name()
+        )),
+        (Object::Hash(hash), _) => match index.hash_key() {
+            Some(key) => hash.get(&key).cloned().unwrap_or(Object::Null),
+            None => Object::Error(format!("unusable as hash key: {}", index.type_name())),
+        },
+        _ => Object::Error(format!(
+            "index operator not supported: {}",
+            left.type_name()
+        )),
+    }
+}
+
+fn eval_slice_expression(left: Object, start: Option<Object>, end: Option<Object>) -> Object {
+    let elements = match left {
+        Object::Array(elements) => elements,
+        _ => {
+            return Object::Error(format!(
+                "slice operator not supported: {}",
+                left.type_name()
+            ))
+        }
+    };
+
+    let mut bounds = [0, elements.len() as i64];
+    for (bound, value) in bounds.iter_mut().zip([start, end]) {
+        match value {
+            Some(Object::Integer(value)) => *bound = value,
+            #[cfg(feature = "bignum")]
+            Some(Object::BigInteger(value)) => {
+                return Object::Error(format!(
+                    "slice out of bounds: {} with length {}",
+                    value,
+                    elements.len()
+                ))
+            }
+            Some(value) => {
+                return Object::Error(format!(
+                    "slice bounds must be INTEGER, got {}",
+                    value.type_name()
+                ))
+            }
+            None => {}
+        }
+    }
+
+    let length = elements.len();
+    match (
+        resolve_index(bounds[0], length),
+        resolve_index(bounds[1], length),
+    ) {
+        (Some(start), Some(end)) if start <= end && end <= length => {
+            Object::Array(elements[start..end].to_vec())
+        }
+        _ => Object::Error(format!(
+            "slice out of bounds: [{}:{}] with length {}",
+            bounds[0], bounds[1], length
+        )),
+    }
+}
+
+fn apply_function(function: Object, arguments: Vec<Object>, budget: &Budget) -> Object {
+    let function = match function {
+        Object::Function(function) => function,
+        //Host functions can return large values as well
+        Object::Builtin(builtin) => return budget.check_allocation(builtin.call(arguments)),
+        _ => return Object::Error(format!("not a function: {}", function.type_name())),
+    };
+
+    if function.parameters.len() != arguments.len() {
+        return Object::Error(format!(
+            "wrong number of arguments: want={}, got={}",
+            function.parameters.len(),
+            arguments.len()
+        ));
+    }
+
+    let mut enclosed = Environment::new_enclosed(Rc::clone(&function.env));
+    for (parameter, argument) in function.parameters.iter().zip(arguments) {
+        enclosed.set(&parameter.value, argument);
+    }
+
+    if let Err(error) = budget.enter_call() {
+        return error;
+    }
+    let result = eval_block_statement(&function.body, &Rc::new(RefCell::new(enclosed)), budget);
+    budget.exit_call();
+
+    match result {
+        Object::ReturnValue(value) => *value,
+        result => result,
+    }
+}
+
+fn eval_prefix_expression(operator: &str, right: Object) -> Object {
+    match operator {
+        "!" => Object::Boolean(!is_truthy(&right)),
+        "-" => match right {
+            Object::Integer(value) => match value.checked_neg() {
+                Some(value) => Object::Integer(value),
+                #[cfg(feature = "bignum")]
+                None => Object::from(BigInt::from(value).neg()),
+                #[cfg(not(feature = "bignum"))]
+                None => Object::Error(format!("integer overflow: -({})", value)),
+            },
+            #[cfg(feature = "bignum")]
+            Object::BigInteger(value) => Object::from(value.neg()),
+            Object::Float(value) => Object::Float(-value),
+            _ => Object::Error(format!("unknown operator: -{}", right.type_name())),
+        },
+        "~" => match right {
+            Object::Integer(value) => Object::Integer(!value),
+            #[cfg(feature = "bignum")]
+            Object::BigInteger(value) => Object::from(value.not()),
+            _ => Object::Error(format!("unknown operator: ~{}", right.type_name())),
+        },
+        _ => Object::Error(format!(
+            "unknown operator: {}{}",
+            operator,
+            right.type_name()
+        )),
+    }
+}
+
+fn eval_infix_expression(operator: &str, left: Object, right: Object) -> Object {
+    //Only reached if the left side did not short-circuit, see eval_expression
+    if operator == "&&" || operator == "||" {
+        return Object::Boolean(is_truthy(&right));
+    }
+
+    match (&left, &right) {
+        (Object::Integer(left), Object::Integer(right)) => {
+            eval_integer_infix_expression(operator, *left, *right)
+        }
+        //Mixing integers and floats gives a float
+        (Object::Float(left), Object::Float(right)) => {
+            eval_float_infix_expression(operator, *left, *right)
+        }
+        (Object::Integer(left), Object::Float(right)) => {
+            eval_float_infix_expression(operator, *left as f64, *right)
+        }
+        (Object::Float(left), Object::Integer(right)) => {
+            eval_float_infix_expression(operator, *left, *right as f64)
+        }
+        #[cfg(feature = "bignum")]
+        (Object::BigInteger(left), Object::BigInteger(right)) => {
+            eval_big_integer_infix_expression(operator, left.clone(), right.clone())
+        }
+        #[cfg(feature = "bignum")]
+        (Object::BigInteger(left), Object::Integer(right)) => {
+            eval_big_integer_infix_expression(operator, left.clone(), BigInt::from(*right))
+        }
+        #[cfg(feature = "bignum")]
+        (Object::Integer(left), Object::BigInteger(right)) => {
+            eval_big_integer_infix_expression(operator, BigInt::from(*left), right.clone())
+        }
+        #[cfg(feature = "bignum")]
+        (Object::BigInteger(left), Object::Float(right)) => {
+            eval_float_infix_expression(operator, left.to_f64(), *right)
+        }
+        #[cfg(feature = "bignum")]
+        (Object::Float(left), Object::BigInteger(right)) => {
+            eval_float_infix_expression(operator, *left, right.to_f64())
+        }
+        (Object::String(left), Object::String(right)) => {
+            eval_string_infix_expression(operator, left, right)
+        }
+        (Object::Boolean(left), Object::Boolean(right)) => match operator {
+            "==" => Object::Boolean(left == right),
+            "!=" => Object::Boolean(left != right),
+            _ => Object::Error(format!("unknown operator: BOOLEAN {} BOOLEAN", operator)),
+        },
+        _ if left.type_name() != right.type_name() => Object::Error(format!(
+            "type mismatch: {} {} {}",
+            left.type_name(),
+            operator,
+            right.type_name()
+        )),
+        _ => Object::Error(format!(
+            "unknown operator: {} {} {}",
+            left.type_name(),
+            operator,
+            right.type_name()
+        )),
+    }
+}
+
+//Arithmetic is checked, overflowing is a runtime error rather than wrapping
+fn eval_integer_infix_expression(operator: &str, left: i64, right: i64) -> Object {
+    let result = match operator {
+        "+" => left.checked_add(right),
+        "-" => left.checked_sub(right),
+        "*" => left.checked_mul(right),
+        "/" | "%" if right == 0 => return Object::Error("division by zero".to_string()),
+        "/" => left.checked_div(right),
+        "%" => left.checked_rem(right),
+        "**" => {
+            if right < 0 {
+                return Object::Error(format!("negative exponent: {}", right));
+            }
+            match u32::try_from(right) {
+                Ok(right) => left.checked_pow(right),
+                //Powers of these stay small however large the exponent
+                Err(_) => match left {
+                    0 | 1 => Some(left),
+                    -1 if right % 2 == 0 => Some(1),
+                    -1 => Some(-1),
+                    _ => None,
+                },
+            }
+        }
+        "&" => Some(left & right),
+        "|" => Some(left | right),
+        "^" => Some(left ^ right),
+        "<<" | ">>" if !(0..64).contains(&right) => {
+            return shift_out_of_range(operator, left, right)
+        }
+        ">>" => Some(left >> right),
+        //Shifting out any bit that differs from the sign is an overflow
+        "<<" => {
+            let shifted = left << right;
+            (shifted >> right == left).then_some(shifted)
+        }
+        "<" => return Object::Boolean(left < right),
+        ">" => return Object::Boolean(left > right),
+        "<=" => return Object::Boolean(left <= right),
+        ">=" => return Object::Boolean(left >= right),
+        "==" => return Object::Boolean(left == right),
+        "!=" => return Object::Boolean(left != right),
+        _ => return Object::Error(format!("unknown operator: INTEGER {} INTEGER", operator)),
+    };
+
+    match result {
+        Some(value) => Object::Integer(value),
+        None => integer_overflow(operator, left, right),
+    }
+}
+
+//Redoes the operation with big integers
+#[cfg(feature = "bignum")]
+fn integer_overflow(operator: &str, left: i64, right: i64) -> Object {
+    eval_big_integer_infix_expression(operator, BigInt::from(left), BigInt::from(right))
+}
+
+#[cfg(not(feature = "bignum"))]
+fn integer_overflow(operator: &str, left: i64, right: i64) -> Object {
+    Object::Error(format!("integer overflow: {} {} {}", left, operator, right))
+}
+
+//Shift counts are limited to the width of an i64, also for big integers
+fn shift_out_of_range(operator: &str, left: impl Display, right: impl Display) -> Object {
+    Object::Error(format!(
+        "shift out of range: {} {} {}",
+        left, operator, right
+    ))
+}
+
+#[cfg(feature = "bignum")]
+fn eval_big_integer_infix_expression(operator: &str, left: BigInt, right: BigInt) -> Object {
+    let result = match operator {
+        "+" => left.add(&right),
+        "-" => left.sub(&right),
+        "*" => left.mul(&right),
+        "/" | "%" => match left.div_rem(&right) {
+            Some((quotient, _)) if operator == "/" => quotient,
+            Some((_, remainder)) => remainder,
+            None => return Object::Error("division by zero".to_string()),
+        },
+        "**" => {
+            if right.is_negative() {
+                return Object::Error(format!("negative exponent: {}", right));
+            }
+            match right.to_i64().and_then(|right| u32::try_from(right).ok()) {
+                Some(exponent) => left.pow(exponent),
+                None => match left.to_i64() {
+                    Some(0 | 1) => left,
+                    Some(-1) if right.and(&BigInt::from(1)).is_zero() => BigInt::from(1),
+                    Some(-1) => left,
+                    _ => return Object::Error(format!("exponent too large: {}", right)),
+                },
+            }
+        }
+        "&" => left.and(&right),
+        "|" => left.or(&right),
+        "^" => left.xor(&right),
+        "<<" | ">>" => match right.to_i64().and_then(|right| u32::try_from(right).ok()) {
+            Some(shift) if shift < 64 && operator == "<<" => left.shl(shift),
+            Some(shift) if shift < 64 => left.shr(shift),
+            _ => return shift_out_of_range(operator, left, right),
+        },
+        "<" => return Object::Boolean(left < right),
+        ">" => return Object::Boolean(left > right),
+        "<=" => return Object::Boolean(left <= right),
+        ">=" => return Object::Boolean(left >= right),
+        "==" => return Object::Boolean(left == right),
+        "!=" => return Object::Boolean(left != right),
+        _ => return Object::Error(format!("unknown operator: INTEGER {} INTEGER", operator)),
+    };
+
+    Object::from(result)
+}
+
+//Follows IEEE 754, so dividing by zero gives an infinity or NaN rather than
+//an error
+fn eval_float_infix_expression(operator: &str, left: f64, right: f64) -> Object {
+    match operator {
+        "+" => Object::Float(left + right),
+        "-" => Object::Float(left - right),
+        "*" => Object::Float(left * right),
+        "/" => Object::Float(left / right),
+        "%" => Object::Float(left % right),
+        "**" => Object::Float(left.powf(right)),
+        "<" => Object::Boolean(left < right),
+        ">" => Object::Boolean(left > right),
+        "<=" => Object::Boolean(left <= right),
+        ">=" => Object::Boolean(left >= right),
+        "==" => Object::Boolean(left == right),
+        "!=" => Object::Boolean(left != right),
+        _ => Object::Error(format!("unknown operator: FLOAT {} FLOAT", operator)),
+    }
+}
+
+fn eval_string_infix_expression(operator: &str, left: &str, right: &str) -> Object {
+    match operator {
+        "+" => Object::String(format!("{}{}", left, right)),
+        "==" => Object::Boolean(left == right),
+        "!=" => Object::Boolean(left != right),
+        _ => Object::Error(format!("unknown operator: STRING {} STRING", operator)),
+    }
+}
+
+//Helper
+fn is_truthy(object: &Object) -> bool {
+    !matches!(object, Object::Null | Object::Boolean(false))
+}
+
+//Negative indices count from the end. None if the index is before the start,
+//indices past the end are returned as is for the caller to check.
+fn resolve_index(index: i64, length: usize) -> Option<usize> {
+    if index < 0 {
+        let position = length as i64 + index;
+        usize::try_from(position).ok()
+    } else {
+        usize::try_from(index).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{lexer::Lexer, parser::Parser};
+
+    #[test]
+    fn eval_integer_expression_test() {
+        let test_inputs = vec![
+            ("5", 5),
+            ("10", 10),
+            ("-5", -5),
+            ("-10", -10),
+            ("5 + 5 + 5 + 5 - 10", 10),
+            ("2 * 2 * 2 * 2 * 2", 32),
+            ("-50 + 100 + -50", 0),
+            ("5 * 2 + 10", 20),
+            ("5 + 2 * 10", 25),
+            ("50 / 2 * 2 + 10", 60),
+            ("2 * (5 + 10)", 30),
+            ("3 * (3 * 3) + 10", 37),
+            ("(5 + 10 * 2 + 15 / 3) * 2 + -10", 50),
+            ("17 % 5", 2),
+            ("-17 % 5", -2),
+            ("2 ** 10", 1024),
+            ("2 ** 3 ** 2", 512),
+            ("-2 ** 2", -4),
+            ("(-2) ** 2", 4),
+            ("0xFF + 0o17 + 0b11", 273),
+            ("1_000 * 1_000", 1_000_000),
+            ("7 ** 0", 1),
+            ("0 ** 0", 1),
+            ("0 ** 5000000000", 0),
+            ("1 ** 5000000000", 1),
+            ("(-1) ** 5000000000", 1),
+            ("(-1) ** 5000000001", -1),
+            ("10 - 3 % 2 * 4", 6),
+            ("9223372036854775807", i64::MAX),
+            ("-9223372036854775807 - 1", i64::MIN),
+            ("let min = -9223372036854775807 - 1; min / 1", i64::MIN),
+        ];
+
+        for input in test_inputs.iter() {
+            assert_eq!(Object::Integer(input.1), test_eval(input.0), "{}", input.0);
+        }
+    }
+
+    #[test]
+    fn eval_boolean_expression_test() {
+        let test_inputs = vec![
+            ("true", true),
+            ("false", false),
+            ("1 < 2", true),
+            ("1 > 2", false),
+            ("1 == 1", true),
+            ("1 != 1", false),
+            ("true == true", true),
+            ("true != false", true),
+            ("(1 < 2) == true", true),
+            ("(1 > 2) == true", false),
+            ("!true", false),
+            ("!!true", true),
+            ("!5", false),
+            ("!!5", true),
+            ("1 <= 2", true),
+            ("2 <= 2", true),
+            ("3 <= 2", false),
+            ("1 >= 2", false),
+            ("2 >= 2", true),
+            ("true && true", true),
+            ("true && false", false),
+            ("false || true", true),
+            ("false || false", false),
+            ("1 < 2 && 2 < 3", true),
+            ("1 > 2 || 2 > 3", false),
+            ("5 && \"a\"", true),
+            ("false || !true", false),
+        ];
+
+        for input in test_inputs.iter() {
+            assert_eq!(Object::Boolean(input.1), test_eval(input.0), "{}", input.0);
+        }
+    }
+
+    #[test]
+    fn eval_float_expression_test() {
+        let test_inputs = [
+            ("2.75", Object::Float(2.75)),
+            ("-2.5", Object::Float(-2.5)),
+            ("1e3", Object::Float(1000.0)),
+            ("0.1 + 0.2 > 0.3", Object::Boolean(true)),
+            ("1.5 * 2", Object::Float(3.0)),
+            ("1 + 0.5", Object::Float(1.5)),
+            ("7 / 2", Object::Integer(3)),
+            ("7 / 2.0", Object::Float(3.5)),
+            ("7.5 % 2", Object::Float(1.5)),
+            ("2 ** 0.5 == 2.0 ** 0.5", Object::Boolean(true)),
+            ("4.0 ** -1", Object::Float(0.25)),
+            ("1 == 1.0", Object::Boolean(true)),
+            ("1 != 1.5", Object::Boolean(true)),
+            ("2 <= 1.5", Object::Boolean(false)),
+            ("1.0 / 0", Object::Float(f64::INFINITY)),
+            ("let x = 1; x += 0.5; x", Object::Float(1.5)),
+        ];
+
+        for input in test_inputs.iter() {
+            assert_eq!(input.1, test_eval(input.0), "{}", input.0);
+        }
+
+        assert_eq!("2.0", test_eval("1.0 * 2").to_string());
+        assert_eq!("[1, 1.5]", test_eval("[1, 1.5]").to_string());
+    }
+
+    #[test]
+    fn eval_conversion_builtin_test() {
+        let test_inputs = [
+            ("int(3.99)", Object::Integer(3)),
+            ("int(-3.99)", Object::Integer(-3)),
+            ("int(\" 42 \")", Object::Integer(42)),
+            ("int(true)", Object::Integer(1)),
+            ("int(7)", Object::Integer(7)),
+            ("float(2)", Object::Float(2.0)),
+            ("float(\"1e-3\")", Object::Float(0.001)),
+            ("str(1.5)", Object::String("1.5".to_string())),
+            ("str([1, \"a\"])", Object::String("[1, \"a\"]".to_string())),
+            ("float(1) / 2", Object::Float(0.5)),
+            ("let int = fn(x) { 0 }; int(5.5)", Object::Integer(0)),
+            (
+                "int(1e19)",
+                Object::Error("cannot convert 1e19 to INTEGER".to_string()),
+            ),
+            (
+                "int(\"abc\")",
+                Object::Error("cannot convert \"abc\" to INTEGER".to_string()),
+            ),
+            (
+                "float(\"x\")",
+                Object::Error("cannot convert \"x\" to FLOAT".to_string()),
+            ),
+            (
+                "int([1])",
+                Object::Error("argument to `int` not supported, got ARRAY".to_string()),
+            ),
+            (
+                "float(1, 2)",
+                Object::Error("wrong number of arguments: want=1, got=2".to_string()),
+            ),
+        ];
+
+        for input in test_inputs.iter() {
+            assert_eq!(input.1, test_eval(input.0), "{}", input.0);
+        }
+
+        assert_eq!("builtin int", test_eval("int").to_string());
+    }
+
+    #[test]
+    fn eval_short_circuit_test() {
+        let test_inputs = [
+            //The right side would be an error or change x if it ran
+            ("let x = 1; false && (x = 2); x", Object::Integer(1)),
+            ("let x = 1; true || (x = 2); x", Object::Integer(1)),
+            ("let x = 1; true && (x = 2); x", Object::Integer(2)),
+            ("let x = 1; false || (x = 2); x", Object::Integer(2)),
+            ("false && y", Object::Boolean(false)),
+            ("true || y", Object::Boolean(true)),
+            (
+                "true && y",
+                Object::Error("identifier not found: y".to_string()),
+            ),
+            (
+                "let f = fn(n) { n > 0 && f(n - 1) }; f(3)",
+                Object::Boolean(false),
+            ),
+        ];
+
+        for input in test_inputs.iter() {
+            assert_eq!(input.1, test_eval(input.0), "{}", input.0);
+        }
+    }
+
+    #[test]
+    fn eval_string_expression_test() {
+        let test_inputs = [
+            (
+                "\"Hello World!\"",
+                Object::String("Hello World!".to_string()),
+            ),
+            (
+                "\"Hello\" + \" \" + \"World!\"",
+                Object::String("Hello World!".to_string()),
+            ),
+            (
+                "let s = \"a\"; s += \"\\u{e9}\\n\"; s",
+                Object::String("aé\n".to_string()),
+            ),
+            ("\"abc\" == \"abc\"", Object::Boolean(true)),
+            ("\"abc\" == \"abd\"", Object::Boolean(false)),
+            ("\"abc\" != \"abd\"", Object::Boolean(true)),
+            (
+                "\"a\" - \"b\"",
+                Object::Error("unknown operator: STRING - STRING".to_string()),
+            ),
+            (
+                "\"a\" + 1",
+                Object::Error("type mismatch: STRING + INTEGER".to_string()),
+            ),
+        ];
+
+        for input in test_inputs.iter() {
+            assert_eq!(input.1, test_eval(input.0), "{}", input.0);
+        }
+    }
+
+    #[test]
+    fn eval_array_literal_test() {
+        let result = test_eval("[1, 2 * 2, 3 + 3, \"a\"]");
+
+        assert_eq!(
+            Object::Array(vec![
+                Object::Integer(1),
+                Object::Integer(4),
+                Object::Integer(6),
+                Object::String("a".to_string())
+            ]),
+            result
+        );
+        assert_eq!("[1, 4, 6, \"a\"]", result.to_string());
+    }
+
+    #[test]
+    fn eval_index_expression_test() {
+        let test_inputs = [
+            ("[1, 2, 3][0]", 1),
+            ("[1, 2, 3][1]", 2),
+            ("[1, 2, 3][2]", 3),
+            ("let i = 0; [1][i];", 1),
+            ("[1, 2, 3][1 + 1];", 3),
+            ("let myArray = [1, 2, 3]; myArray[2];", 3),
+            (
+                "let myArray = [1, 2, 3]; myArray[0] + myArray[1] + myArray[2];",
+                6,
+            ),
+            ("[1, 2, 3][-1]", 3),
+            ("[1, 2, 3][-3]", 1),
+            ("[[1, 2], [3, 4]][1][0]", 3),
+            ("let f = fn() { [10, 20] }; f()[1]", 20),
+        ];
+
+        for input in test_inputs.iter() {
+            assert_eq!(Object::Integer(input.1), test_eval(input.0), "{}", input.0);
+        }
+    }
+
+    #[test]
+    fn eval_slice_expression_test() {
+        let test_inputs = [
+            ("[1, 2, 3, 4][1:3]", vec![2, 3]),
+            ("[1, 2, 3, 4][:2]", vec![1, 2]),
+            ("[1, 2, 3, 4][2:]", vec![3, 4]),
+            ("[1, 2, 3, 4][:]", vec![1, 2, 3, 4]),
+            ("[1, 2, 3, 4][-2:]", vec![3, 4]),
+            ("[1, 2, 3, 4][:-1]", vec![1, 2, 3]),
+            ("[1, 2, 3, 4][2:2]", vec![]),
+            ("[1, 2, 3, 4][4:]", vec![]),
+        ];
+
+        for input in test_inputs.iter() {
+            let expected = input.1.iter().map(|v| Object::Integer(*v)).collect();
+            assert_eq!(Object::Array(expected), test_eval(input.0), "{}", input.0);
+        }
+    }
+
+    #[test]
+    fn eval_index_errors_test() {
+        let test_inputs = [
+            ("[1, 2, 3][3]", "index out of bounds: index 3, length 3"),
+            ("[1, 2, 3][-4]", "index out of bounds: index -4, length 3"),
+            ("[][0]", "index out of bounds: index 0, length 0"),
+            ("1[0]", "index operator not supported: INTEGER"),
+            ("[1][true]", "index must be an INTEGER, got BOOLEAN"),
+            ("[1, 2][1:3]", "slice out of bounds: [1:3] with length 2"),
+            ("[1, 2][2:1]", "slice out of bounds: [2:1] with length 2"),
+            ("[1, 2][-3:]", "slice out of bounds: [-3:2] with length 2"),
+            ("[1, 2][\"a\":]", "slice bounds must be INTEGER, got STRING"),
+            ("\"ab\"[0:1]", "slice operator not supported: STRING"),
+        ];
+
+        for input in test_inputs.iter() {
+            assert_eq!(
+                Object::Error(input.1.to_string()),
+                test_eval(input.0),
+                "{}",
+                input.0
+            );
+        }
+    }
+
+    #[test]
+    fn eval_hash_literal_test() {
+        let input = "let two = \"two\";
+            {
+                \"one\": 10 - 9,
+                two: 1 + 1,
+                \"thr\" + \"ee\": 6 / 2,
+                4: 4,
+                true: 5,
+                false: 6
+            }";
+
+        let result = test_eval(input);
+        if let Object::Hash(hash) = &result {
+            let pairs: Vec<(Object, Object)> =
+                hash.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+            assert_eq!(
+                vec![
+                    (Object::String("one".to_string()), Object::Integer(1)),
+                    (Object::String("two".to_string()), Object::Integer(2)),
+                    (Object::String("three".to_string()), Object::Integer(3)),
+                    (Object::Integer(4), Object::Integer(4)),
+                    (Object::Boolean(true), Object::Integer(5)),
+                    (Object::Boolean(false), Object::Integer(6)),
+                ],
+                pairs
+            );
+        } else {
+            panic!("expected a hash, got {:?}", result);
+        }
+    }
+
+    #[test]
+    fn eval_hash_insertion_order_test() {
+        let result = test_eval("{\"b\": 1, \"a\": 2, 3: [true], \"b\": 4}");
+
+        assert_eq!("{\"b\": 4, \"a\": 2, 3: [true]}", result.to_string());
+    }
+
+    #[test]
+    fn eval_hash_index_expression_test() {
+        let test_inputs = [
+            ("{\"foo\": 5}[\"foo\"]", Object::Integer(5)),
+            ("{\"foo\": 5}[\"bar\"]", Object::Null),
+            ("let key = \"foo\"; {\"foo\": 5}[key]", Object::Integer(5)),
+            ("{}[\"foo\"]", Object::Null),
+            ("{5: 5}[5]", Object::Integer(5)),
+            ("{true: 5}[true]", Object::Integer(5)),
+            ("{false: 5}[false]", Object::Integer(5)),
+            ("{1: {\"a\": [7]}}[1][\"a\"][0]", Object::Integer(7)),
+        ];
+
+        for input in test_inputs.iter() {
+            assert_eq!(input.1, test_eval(input.0), "{}", input.0);
+        }
+    }
+
+    #[test]
+    fn eval_hash_key_errors_test() {
+        let test_inputs = [
+            (
+                "{\"name\": \"Monkey\"}[fn(x) { x }];",
+                "unusable as hash key: FUNCTION",
+            ),
+            ("{[1]: 2}", "unusable as hash key: ARRAY"),
+            ("{{}: 2}", "unusable as hash key: HASH"),
+            ("{fn() { 1 }: 2}", "unusable as hash key: FUNCTION"),
+            ("{1: 2}[[1]]", "unusable as hash key: ARRAY"),
+        ];
+
+        for input in test_inputs.iter() {
+            assert_eq!(
+                Object::Error(input.1.to_string()),
+                test_eval(input.0),
+                "{}",
+                input.0
+            );
+        }
+    }
+
+    #[test]
+    fn error_handling_test() {
+        let test_inputs = [
+            ("5 + true;", "type mismatch: INTEGER + BOOLEAN"),
+            ("5 + true; 5;", "type mismatch: INTEGER + BOOLEAN"),
+            ("-true", "unknown operator: -BOOLEAN"),
+            ("true + false;", "unknown operator: BOOLEAN + BOOLEAN"),
+            ("5; true + false; 5", "unknown operator: BOOLEAN + BOOLEAN"),
+            ("10 / 0", "division by zero"),
+            ("10 % 0", "division by zero"),
+            ("2 ** -1", "negative exponent: -1"),
+            ("true <= false", "unknown operator: BOOLEAN <= BOOLEAN"),
+            ("\"a\" % \"b\"", "unknown operator: STRING % STRING"),
+            ("1 >= true", "type mismatch: INTEGER >= BOOLEAN"),
+            ("0 % 0", "division by zero"),
+        ];
+
+        for input in test_inputs.iter() {
+            assert_eq!(
+                Object::Error(input.1.to_string()),
+                test_eval(input.0),
+                "{}",
+                input.0
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(not(feature = "bignum"))]
+    fn integer_overflow_test() {
+        let test_inputs = [
+            ("2 ** 63", "integer overflow: 2 ** 63"),
+            (
+                "9223372036854775807 + 1",
+                "integer overflow: 9223372036854775807 + 1",
+            ),
+            (
+                "-9223372036854775807 - 2",
+                "integer overflow: -9223372036854775807 - 2",
+            ),
+            (
+                "4611686018427387904 * 2",
+                "integer overflow: 4611686018427387904 * 2",
+            ),
+            (
+                "let min = -9223372036854775807 - 1; min / -1",
+                "integer overflow: -9223372036854775808 / -1",
+            ),
+            (
+                "let min = -9223372036854775807 - 1; min % -1",
+                "integer overflow: -9223372036854775808 % -1",
+            ),
+            (
+                "let min = -9223372036854775807 - 1; -min",
+                "integer overflow: -(-9223372036854775808)",
+            ),
+            (
+                "let x = 9223372036854775807; x += 1",
+                "integer overflow: 9223372036854775807 + 1",
+            ),
+            ("2 ** 5000000000", "integer overflow: 2 ** 5000000000"),
+            ("1 << 63", "integer overflow: 1 << 63"),
+            ("-3 << 62", "integer overflow: -3 << 62"),
+        ];
+
+        for input in test_inputs.iter() {
+            assert_eq!(
+                Object::Error(input.1.to_string()),
+                test_eval(input.0),
+                "{}",
+                input.0
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "bignum")]
+    fn eval_big_integer_test() {
+        let test_inputs = [
+            ("9223372036854775807 + 1", "9223372036854775808"),
+            ("-9223372036854775807 - 2", "-9223372036854775809"),
+            ("2 ** 100", "1267650600228229401496703205376"),
+            ("0x1_0000_0000_0000_0000", "18446744073709551616"),
+            (
+                "let fact = fn(n) { if (n < 2) { 1 } else { n * fact(n - 1) } }; fact(30)",
+                "265252859812191058636308480000000",
+            ),
+            (
+                "let min = -9223372036854775807 - 1; -min",
+                "9223372036854775808",
+            ),
+            (
+                "let min = -9223372036854775807 - 1; min / -1",
+                "9223372036854775808",
+            ),
+            ("let min = -9223372036854775807 - 1; min % -1", "0"),
+            ("123456789012345678901234567890 % 1000", "890"),
+            (
+                "-(123456789012345678901234567890)",
+                "-123456789012345678901234567890",
+            ),
+            ("int(\"99999999999999999999\") + 1", "100000000000000000000"),
+            ("str(2 ** 64)", "18446744073709551616"),
+            ("float(2 ** 64)", "1.8446744073709552e19"),
+            ("2 ** 64 * 0.5", "9.223372036854776e18"),
+            (
+                "[1, 2][2 ** 64]",
+                "ERROR: index out of bounds: index 18446744073709551616, length 2",
+            ),
+            ("2 ** 64 / 0", "ERROR: division by zero"),
+            (
+                "2 ** 5000000000",
+                "ERROR: integer size limit exceeded: 625000001 bytes over 8192",
+            ),
+            (
+                "2 ** (2 ** 64)",
+                "ERROR: exponent too large: 18446744073709551616",
+            ),
+            ("1 ** (2 ** 64)", "1"),
+            ("(-1) ** (2 ** 64 + 1)", "-1"),
+            ("2 ** 64 + true", "ERROR: type mismatch: INTEGER + BOOLEAN"),
+        ];
+
+        for input in test_inputs.iter() {
+            assert_eq!(input.1, test_eval(input.0).to_string(), "{}", input.0);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "bignum")]
+    fn big_integer_interop_test() {
+        //Results that fit again are plain integers
+        let test_inputs = [
+            ("(2 ** 64) / (2 ** 60)", Object::Integer(16)),
+            ("9223372036854775807 + 1 - 1", Object::Integer(i64::MAX)),
+            ("2 ** 64 > 5", Object::Boolean(true)),
+            ("-(2 ** 64) < -5", Object::Boolean(true)),
+            ("2 ** 64 == 2 ** 64", Object::Boolean(true)),
+            ("2 ** 64 == 18446744073709551616", Object::Boolean(true)),
+            ("2 ** 64 != 2 ** 63", Object::Boolean(true)),
+            ("(2 ** 64 - 1) + 1 == 2 ** 64", Object::Boolean(true)),
+            (
+                "{2 ** 64: \"big\"}[18446744073709551616]",
+                Object::String("big".to_string()),
+            ),
+            (
+                "{5: \"small\"}[2 ** 64 - 18446744073709551611]",
+                Object::String("small".to_string()),
+            ),
+            ("(2 ** 64 + 5) & 0xFF", Object::Integer(5)),
+            ("(2 ** 64) >> 60", Object::Integer(16)),
+            ("-(2 ** 64) >> 63", Object::Integer(-2)),
+            ("~(2 ** 64) + 2 ** 64", Object::Integer(-1)),
+            ("(2 ** 64 | 1) ^ 2 ** 64", Object::Integer(1)),
+            (
+                "1 << 63",
+                Object::BigInteger(BigInt::parse("9223372036854775808").unwrap()),
+            ),
+            (
+                "2 ** 64 << 64",
+                Object::Error("shift out of range: 18446744073709551616 << 64".to_string()),
+            ),
+        ];
+
+        for input in test_inputs.iter() {
+            assert_eq!(input.1, test_eval(input.0), "{}", input.0);
+        }
+    }
+
+    #[test]
+    fn eval_bitwise_test() {
+        let test_inputs = [
+            ("0b1100 & 0b1010", Object::Integer(0b1000)),
+            ("0b1100 | 0b1010", Object::Integer(0b1110)),
+            ("0b1100 ^ 0b1010", Object::Integer(0b0110)),
+            ("~0", Object::Integer(-1)),
+            ("~5", Object::Integer(-6)),
+            ("-1 & 0xFF", Object::Integer(0xFF)),
+            ("1 << 10", Object::Integer(1024)),
+            ("-1 << 62", Object::Integer(-1 << 62)),
+            ("1024 >> 3", Object::Integer(128)),
+            ("-16 >> 2", Object::Integer(-4)),
+            ("-1 >> 63", Object::Integer(-1)),
+            (
+                "let header = 0xABCD; (header >> 8) & 0xFF",
+                Object::Integer(0xAB),
+            ),
+            (
+                "0x12 & 0xF0 == 0x10",
+                Object::Error("type mismatch: INTEGER & BOOLEAN".to_string()),
+            ),
+            ("(0x12 & 0xF0) == 0x10", Object::Boolean(true)),
+            (
+                "1 << 64",
+                Object::Error("shift out of range: 1 << 64".to_string()),
+            ),
+            (
+                "1 >> -1",
+                Object::Error("shift out of range: 1 >> -1".to_string()),
+            ),
+            (
+                "1.5 & 1",
+                Object::Error("unknown operator: FLOAT & FLOAT".to_string()),
+            ),
+            (
+                "true | false",
+                Object::Error("unknown operator: BOOLEAN | BOOLEAN".to_string()),
+            ),
+            (
+                "~\"a\"",
+                Object::Error("unknown operator: ~STRING".to_string()),
+            ),
+        ];
+
+        for input in test_inputs.iter() {
+            assert_eq!(input.1, test_eval(input.0), "{}", input.0);
+        }
+    }
+
+    #[test]
+    fn eval_return_statements_test() {
+        let test_inputs = [
+            ("return 10;", 10),
+            ("return 10; 9;", 10),
+            ("return 2 * 5; 9;", 10),
+            ("9; return 2 * 5; 9;", 10),
+            ("return 10", 10),
+        ];
+
+        for input in test_inputs.iter() {
+            assert_eq!(Object::Integer(input.1), test_eval(input.0), "{}", input.0);
+        }
+    }
+
+    #[test]
+    fn eval_let_statements_test() {
+        let test_inputs = [
+            ("let a = 5; a;", 5),
+            ("let a = 5 * 5; a;", 25),
+            ("let a = 5; let b = a; b;", 5),
+            ("let a = 5; let b = a; let c = a + b + 5; c;", 15),
+            ("let a = 5\n a", 5),
+        ];
+
+        for input in test_inputs.iter() {
+            assert_eq!(Object::Integer(input.1), test_eval(input.0), "{}", input.0);
+        }
+    }
+
+    #[test]
+    fn eval_if_else_expression_test() {
+        let test_inputs = [
+            ("if (true) { 10 }", Object::Integer(10)),
+            ("if (false) { 10 }", Object::Null),
+            ("if (1) { 10 }", Object::Integer(10)),
+            ("if (1 < 2) { 10 }", Object::Integer(10)),
+            ("if (1 > 2) { 10 }", Object::Null),
+            ("if (1 > 2) { 10 } else { 20 }", Object::Integer(20)),
+            ("if (1 < 2) { 10 } else { 20 }", Object::Integer(10)),
+            ("if (1 < 2) { 10; 11 } else { 20 }", Object::Integer(11)),
+            (
+                "if (1 > 2) { 1 } else if (2 > 3) { 2 } else { 3 }",
+                Object::Integer(3),
+            ),
+            (
+                "if (1 > 2) { 1 } else if (2 < 3) { 2 } else { 3 }",
+                Object::Integer(2),
+            ),
+            ("if (false) { 1 } else if (false) { 2 }", Object::Null),
+            (
+                "let x = if (true) { 5 } else { 6 }; x * 2",
+                Object::Integer(10),
+            ),
+        ];
+
+        for input in test_inputs.iter() {
+            assert_eq!(input.1, test_eval(input.0), "{}", input.0);
+        }
+    }
+
+    #[test]
+    fn eval_nested_return_test() {
+        let test_inputs = [
+            ("if (10 > 1) { if (10 > 1) { return 10; } return 1; }", 10),
+            (
+                "let f = fn(x) { if (x > 1) { if (x > 2) { return 3; } return 2; } 1 }; f(5) * 100 + f(2) * 10 + f(0)",
+                321,
+            ),
+        ];
+
+        for input in test_inputs.iter() {
+            assert_eq!(Object::Integer(input.1), test_eval(input.0), "{}", input.0);
+        }
+    }
+
+    #[test]
+    fn eval_recursion_test() {
+        let test_inputs = [
+            (
+                "let fib = fn(n) { if (n < 2) { n } else { fib(n - 1) + fib(n - 2) } }; fib(15)",
+                610,
+            ),
+            (
+                "let sum = fn(n, acc) { if (n == 0) { return acc; } sum(n - 1, acc + n) }; sum(100, 0)",
+                5050,
+            ),
+        ];
+
+        for input in test_inputs.iter() {
+            assert_eq!(Object::Integer(input.1), test_eval(input.0), "{}", input.0);
+        }
+    }
+
+    #[test]
+    fn eval_assign_expression_test() {
+        let test_inputs = [
+            ("let a = 1; a = 2; a", 2),
+            ("let a = 1; a = a + 1", 2),
+            ("let a = 10; a += 5; a", 15),
+            ("let a = 10; a -= 5; a", 5),
+            ("let a = 10; a *= 5; a", 50),
+            ("let a = 10; a /= 5; a", 2),
+            ("let a = 1; let b = 2; a = b = 7; a + b", 14),
+            ("let c = 0; let inc = fn() { c += 1 }; inc(); inc(); c", 2),
+            ("let c = 0; let f = fn(c) { c = 5; c }; f(1) + c", 5),
+            (
+                "let counter = fn() { let n = 0; fn() { n += 1 } }; let next = counter(); next(); next(); next()",
+                3,
+            ),
+        ];
+
+        for input in test_inputs.iter() {
+            assert_eq!(Object::Integer(input.1), test_eval(input.0), "{}", input.0);
+        }
+    }
+
+    #[test]
+    fn eval_assign_errors_test() {
+        let test_inputs = [
+            ("x = 1", "identifier not found: x"),
+            ("y += 1", "identifier not found: y"),
+            ("let a = 1; a += true", "type mismatch: INTEGER + BOOLEAN"),
+            ("let a = 1; a /= 0", "division by zero"),
+        ];
+
+        for input in test_inputs.iter() {
+            assert_eq!(
+                Object::Error(input.1.to_string()),
+                test_eval(input.0),
+                "{}",
+                input.0
+            );
+        }
+    }
+
+    #[test]
+    fn eval_function_application_test() {
+        let test_inputs = [
+            ("fn(x) { x; }(5)", 5),
+            ("fn(x) { return x; }(5)", 5),
+            ("fn(x) { x * 2; }(5)", 10),
+            ("fn(x, y) { x + y; }(5, 5)", 10),
+            ("fn(x, y) { x + y; }(5 + 5, fn(z) { z; }(10))", 20),
+            ("fn() { return 1; 2 }()", 1),
+            ("fn() { fn() { return 1; 2 }() + 10 }()", 11),
+        ];
+
+        for input in test_inputs.iter() {
+            assert_eq!(Object::Integer(input.1), test_eval(input.0), "{}", input.0);
+        }
+    }
+
+    #[test]
+    fn eval_closures_test() {
+        let test_inputs = [
+            ("fn(x) { fn(y) { x + y } }(2)(3)", 5),
+            ("fn(f) { f(f(1)) }(fn(x) { x * 3 })", 9),
+            ("fn(x) { fn() { fn() { x } } }(7)()()", 7),
+            (
+                "let newAdder = fn(x) { fn(y) { x + y } }; let addTwo = newAdder(2); addTwo(3);",
+                5,
+            ),
+            (
+                "let twice = fn(f, x) { f(f(x)) }; twice(fn(x) { x * x }, 3)",
+                81,
+            ),
+            (
+                "let sum = fn(n) { fn(m) { m } (n) + n }; let f = fn(n) { return sum(n); }; f(4)",
+                8,
+            ),
+        ];
+
+        for input in test_inputs.iter() {
+            assert_eq!(Object::Integer(input.1), test_eval(input.0), "{}", input.0);
+        }
+    }
+
+    #[test]
+    fn eval_closures_share_body_test() {
+        let result = test_eval("let make = fn(x) { fn() { x } }; [make(1), make(2)]");
+        let functions: Vec<Function> = match result {
+            Object::Array(elements) => elements
+                .into_iter()
+                .map(|element| match element {
+                    Object::Function(function) => function,
+                    _ => panic!("not a function: {}", element),
+                })
+                .collect(),
+            _ => panic!("not an array: {}", result),
+        };
+
+        assert!(Rc::ptr_eq(&functions[0].body, &functions[1].body));
+        assert!(Rc::ptr_eq(
+            &functions[0].parameters,
+            &functions[1].parameters
+        ));
+        assert!(!Rc::ptr_eq(&functions[0].env, &functions[1].env));
+    }
+
+    #[test]
+    fn eval_late_binding_test() {
+        let test_inputs = [
+            (
+                "let later = fn() { defined_after() }; let defined_after = fn() { 42 }; later()",
+                42,
+            ),
+            (
+                "let outer = fn() { let inner = fn(n) { helper(n) }; let helper = fn(n) { n * 2 }; inner(4) }; outer()",
+                8,
+            ),
+        ];
+
+        for input in test_inputs.iter() {
+            assert_eq!(Object::Integer(input.1), test_eval(input.0), "{}", input.0);
+        }
+    }
+
+    #[test]
+    fn eval_function_errors_test() {
+        let test_inputs = [
+            ("foobar", "identifier not found: foobar"),
+            ("5(1)", "not a function: INTEGER"),
+            ("fn(x) { x }()", "wrong number of arguments: want=1, got=0"),
+            ("fn(x) { y }(1)", "identifier not found: y"),
+        ];
+
+        for input in test_inputs.iter() {
+            assert_eq!(
+                Object::Error(input.1.to_string()),
+                test_eval(input.0),
+                "{}",
+                input.0
+            );
+        }
+    }
+
+    //helper
+    fn test_eval(input: &str) -> Object {
+        let lexer = Lexer::new(input);
+        let mut parser = Parser::new(lexer);
+        let programm = parser.parse_programm().unwrap();
+        parser.print_errors();
+
+        eval_programm(&programm, &Rc::new(RefCell::new(Environment::new())))
+    }
+}
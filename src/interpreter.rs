@@ -0,0 +1,402 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{
+    ast::Programm,
+    builtins::Builtin,
+    environment::{Env, Environment},
+    error::ParsingError,
+    eval::eval_programm_with_budget,
+    lexer::Lexer,
+    limits::{Budget, LimitError, Limits},
+    object::Object,
+    parser::Parser,
+    value::{FromValue, IntoValue},
+};
+
+//Handle for embedding the language. Owns the global environment, so host
+//functions, injected globals and bindings made by earlier programms are
+//visible to every programm evaluated through it.
+#[derive(Debug)]
+pub struct Interpreter {
+    env: Env,
+    limits: Limits,
+    limit_exceeded: Option<LimitError>,
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Interpreter::new()
+    }
+}
+
+impl Interpreter {
+    pub fn new() -> Self {
+        Interpreter {
+            env: Rc::new(RefCell::new(Environment::new())),
+            limits: Limits::default(),
+            limit_exceeded: None,
+        }
+    }
+
+    //Applies to each call of eval and run separately
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    //Exposes a Rust closure to scripts. Arguments are converted with
+    //FromValue and the result with IntoValue, a wrong number or type of
+    //arguments is a runtime error in the script.
+    //
+    //    interpreter.register_fn("clamp", |x: i64, max: i64| x.min(max));
+    pub fn register_fn<Args>(&mut self, name: &str, function: impl HostFunction<Args>) {
+        let builtin = function.into_builtin(name);
+        self.env.borrow_mut().set(name, Object::Builtin(builtin));
+    }
+
+    //Globals are ordinary bindings, a script can reassign or shadow them
+    pub fn set_global(&mut self, name: &str, value: impl IntoValue) {
+        self.env.borrow_mut().set(name, value.into_value());
+    }
+
+    pub fn get_global(&self, name: &str) -> Option<Object> {
+        self.env.borrow().get(name)
+    }
+
+    pub fn env(&self) -> &Env {
+        &self.env
+    }
+
+    pub fn eval(&mut self, programm: &Programm) -> Object {
+        let budget = Budget::new(self.limits);
+        let result = eval_programm_with_budget(programm, &self.env, &budget);
+        self.limit_exceeded = budget.exceeded();
+
+        result
+    }
+
+    //The limit that stopped the last evaluation, which returned its message
+    //as an Object::Error. None if it ran within its limits.
+    pub fn limit_exceeded(&self) -> Option<LimitError> {
+        self.limit_exceeded
+    }
+
+    //Parses and evaluates input, nothing is evaluated if it fails to parse
+    pub fn run(&mut self, input: &str) -> Result<Object, Vec<ParsingError>> {
+        let mut parser = Parser::new(Lexer::new(input));
+        let programm = parser.parse_programm().unwrap_or_default();
+
+        match parser.take_errors() {
+            errors if errors.is_empty() => Ok(self.eval(&programm)),
+            errors => Err(errors),
+        }
+    }
+}
+
+//Rust closures that can be registered as a host function. Implemented for
+//closures of up to six arguments that implement FromValue and a result that
+//implements IntoValue. Args only tells the implementations apart.
+pub trait HostFunction<Args> {
+    fn into_builtin(self, name: &str) -> Builtin;
+}
+
+macro_rules! impl_host_function {
+    ($count:literal $(, $arg:ident)*) => {
+        impl<F, R, $($arg,)*> HostFunction<($($arg,)*)> for F
+        where
+            F: Fn($($arg),*) -> R + 'static,
+            R: IntoValue,
+            $($arg: FromValue,)*
+        {
+            #[allow(non_snake_case, unused_mut, unused_variables)]
+            fn into_builtin(self, name: &str) -> Builtin {
+                let builtin_name: Rc<str> = Rc::from(name);
+
+                Builtin::new(name, move |arguments: Vec<Object>| {
+                    if arguments.len() != $count {
+                        return Object::Error(format!(
+                            "wrong number of arguments: want={}, got={}",
+                            $count,
+                            arguments.len()
+                        ));
+                    }
+
+                    let mut arguments = arguments.into_iter().enumerate();
+                    $(
+                        let $arg = match arguments.next().map(convert_argument::<$arg>) {
+                            Some(Ok(value)) => value,
+                            Some(Err(e)) => {
+                                return Object::Error(format!(
+                                    "argument {} to `{}`: {}",
+                                    e.0, builtin_name, e.1
+                                ))
+                            }
+                            None => return Object::Null,
+                        };
+                    )*
+
+                    self($($arg),*).into_value()
+                })
+            }
+        }
+    };
+}
+
+impl_host_function!(0);
+impl_host_function!(1, A);
+impl_host_function!(2, A, B);
+impl_host_function!(3, A, B, C);
+impl_host_function!(4, A, B, C, D);
+impl_host_function!(5, A, B, C, D, E);
+impl_host_function!(6, A, B, C, D, E, G);
+
+//helper
+//Positions in errors count from 1
+fn convert_argument<T: FromValue>((index, value): (usize, Object)) -> Result<T, (usize, String)> {
+    T::from_value(value).map_err(|e| (index + 1, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::limits::DEFAULT_MAX_CALL_DEPTH;
+    use std::time::Duration;
+
+    #[test]
+    fn register_fn_test() {
+        let mut interpreter = Interpreter::new();
+        interpreter.register_fn("answer", || 42);
+        interpreter.register_fn("clamp", |x: i64, max: i64| x.min(max));
+        interpreter.register_fn("join", |parts: Vec<String>, separator: String| {
+            parts.join(&separator)
+        });
+        interpreter.register_fn("checked_sqrt", |x: f64| {
+            if x < 0.0 {
+                Err(format!("negative input: {}", x))
+            } else {
+                Ok(x.sqrt())
+            }
+        });
+
+        let test_inputs = [
+            ("answer()", Object::Integer(42)),
+            ("clamp(10, 3)", Object::Integer(3)),
+            ("clamp(answer(), 100) + 1", Object::Integer(43)),
+            (
+                "join([\"a\", \"b\"], \"-\")",
+                Object::String("a-b".to_string()),
+            ),
+            ("checked_sqrt(16)", Object::Float(4.0)),
+            (
+                "checked_sqrt(-1)",
+                Object::Error("negative input: -1".to_string()),
+            ),
+            (
+                "clamp(1)",
+                Object::Error("wrong number of arguments: want=2, got=1".to_string()),
+            ),
+            (
+                "clamp(1, \"2\")",
+                Object::Error("argument 2 to `clamp`: expected INTEGER, got STRING".to_string()),
+            ),
+            (
+                "join([\"a\", 1], \"\")",
+                Object::Error(
+                    "argument 1 to `join`: element 1: expected STRING, got INTEGER".to_string(),
+                ),
+            ),
+            ("let f = clamp; f(5, 4)", Object::Integer(4)),
+        ];
+
+        for input in test_inputs.iter() {
+            assert_eq!(input.1, interpreter.run(input.0).unwrap(), "{}", input.0);
+        }
+    }
+
+    #[test]
+    fn host_function_state_test() {
+        let log = Rc::new(RefCell::new(vec![]));
+        let mut interpreter = Interpreter::new();
+
+        let sink = Rc::clone(&log);
+        interpreter.register_fn("log", move |message: String| {
+            sink.borrow_mut().push(message);
+        });
+
+        assert_eq!(
+            Object::Null,
+            interpreter.run("log(\"a\"); log(\"b\")").unwrap()
+        );
+        assert_eq!(vec!["a".to_string(), "b".to_string()], *log.borrow());
+    }
+
+    #[test]
+    fn limits_test() {
+        let test_inputs = [
+            (
+                Limits::new().with_max_steps(50),
+                "let f = fn(n) { if (n == 0) { 0 } else { f(n - 1) } }; f(100)",
+                LimitError::Steps(50),
+            ),
+            (
+                Limits::new().with_max_call_depth(10),
+                "let f = fn(n) { if (n == 0) { 0 } else { 1 + f(n - 1) } }; f(10); f(11)",
+                LimitError::CallDepth(10),
+            ),
+            (
+                Limits::new(),
+                "let f = fn(x) { f(x) }; f(1)",
+                LimitError::CallDepth(DEFAULT_MAX_CALL_DEPTH),
+            ),
+            (
+                Limits::new().with_max_expression_depth(20),
+                "let f = fn(n) { if (n == 0) { 0 } else { 1 + f(n - 1) } }; f(5); f(10)",
+                LimitError::ExpressionDepth(20),
+            ),
+            (
+                Limits::new().with_max_allocation(1024),
+                "let grow = fn(s) { grow(s + s) }; grow(\"a\")",
+                LimitError::Allocation {
+                    size: 2048,
+                    max: 1024,
+                },
+            ),
+            (
+                Limits::new().with_max_allocation(3),
+                "let a = [1, 2, 3]; a = [1, 2, 3, 4]",
+                LimitError::Allocation { size: 4, max: 3 },
+            ),
+            (
+                Limits::new().with_max_allocation(3),
+                "{1: 1, 2: 2, 3: 3, 4: 4}",
+                LimitError::Allocation { size: 4, max: 3 },
+            ),
+            (
+                Limits::new().with_max_allocation(7),
+                "let s = \"abc\"; {\"key\": [s, 1]}",
+                LimitError::Allocation { size: 9, max: 7 },
+            ),
+            (
+                Limits::new().with_max_value_depth(3),
+                "let a = [[1]]; {\"key\": a}; [[a]]",
+                LimitError::ValueDepth(3),
+            ),
+            (
+                Limits::new().with_timeout(Duration::from_millis(20)),
+                "let fib = fn(n) { if (n < 2) { n } else { fib(n - 1) + fib(n - 2) } }; fib(40)",
+                LimitError::Timeout(Duration::from_millis(20)),
+            ),
+        ];
+
+        for input in test_inputs.iter() {
+            let mut interpreter = Interpreter::new().with_limits(input.0);
+
+            assert_eq!(
+                Object::Error(input.2.to_string()),
+                interpreter.run(input.1).unwrap(),
+                "{}",
+                input.1
+            );
+            assert_eq!(Some(input.2), interpreter.limit_exceeded(), "{}", input.1);
+
+            //Limits apply to each run on its own
+            assert_eq!(Object::Integer(2), interpreter.run("1 + 1").unwrap());
+            assert_eq!(None, interpreter.limit_exceeded());
+        }
+    }
+
+    #[test]
+    fn host_function_allocation_test() {
+        let mut interpreter = Interpreter::new().with_limits(Limits::new().with_max_allocation(10));
+        interpreter.register_fn("range", |n: i64| (0..n).collect::<Vec<i64>>());
+
+        assert_eq!(Object::Integer(9), interpreter.run("range(10)[9]").unwrap());
+        assert_eq!(
+            Object::Error("allocation limit exceeded: size 11 over 10".to_string()),
+            interpreter.run("range(11)").unwrap()
+        );
+    }
+
+    #[cfg(feature = "bignum")]
+    #[test]
+    fn big_integer_allocation_test() {
+        let limits = Limits::new()
+            .with_max_allocation(1024)
+            .with_max_integer_size(usize::MAX);
+        let mut interpreter = Interpreter::new().with_limits(limits);
+
+        let test_inputs = [
+            ("2 ** 4000000000", 500_000_001),
+            ("(-3) ** 100000", 12_501),
+            ("let x = 2 ** 8000; x * x", 2001),
+        ];
+
+        for input in test_inputs.iter() {
+            let error = LimitError::Allocation {
+                size: input.1,
+                max: 1024,
+            };
+
+            assert_eq!(
+                Object::Error(error.to_string()),
+                interpreter.run(input.0).unwrap(),
+                "{}",
+                input.0
+            );
+        }
+
+        //1001 bytes
+        assert!(!interpreter.run("2 ** 8000").unwrap().is_error());
+    }
+
+    //Big integers are bounded even if only time is limited, a single
+    //operation on them cannot be interrupted
+    #[cfg(feature = "bignum")]
+    #[test]
+    fn big_integer_size_test() {
+        let limits = Limits::new()
+            .with_timeout(Duration::from_millis(100))
+            .with_max_steps(1000);
+        let mut interpreter = Interpreter::new().with_limits(limits);
+
+        let test_inputs = [
+            ("2 ** 100000000", 12_500_001),
+            ("let x = 2 ** 60000; x * x", 15_001),
+            ("let x = 3 ** 40000; x * x * x", 15_850),
+            (&format!("{}0", "9".repeat(20_000)), 8_306),
+        ];
+
+        for input in test_inputs.iter() {
+            let error = LimitError::IntegerSize {
+                size: input.1,
+                max: crate::limits::DEFAULT_MAX_INTEGER_SIZE,
+            };
+
+            assert_eq!(
+                Object::Error(error.to_string()),
+                interpreter.run(input.0).unwrap(),
+                "{}",
+                input.0
+            );
+            assert_eq!(Some(error), interpreter.limit_exceeded(), "{}", input.0);
+        }
+    }
+
+    #[test]
+    fn globals_test() {
+        let mut interpreter = Interpreter::new();
+        interpreter.set_global("limit", 10);
+        interpreter.set_global("names", vec!["a", "b"]);
+
+        assert_eq!(
+            Object::Integer(20),
+            interpreter.run("let doubled = limit * 2; doubled").unwrap()
+        );
+        assert_eq!(Some(Object::Integer(20)), interpreter.get_global("doubled"));
+        assert_eq!(
+            Object::String("b".to_string()),
+            interpreter.run("names[1]").unwrap()
+        );
+        assert!(interpreter.run("let = 1").is_err());
+    }
+}
@@ -1,153 +1,207 @@
-use crate::token::{Token, TokenType};
+use std::borrow::Cow;
 
+use crate::{
+    diagnostic::Diagnostic,
+    span::Span,
+    token::{Token, TokenType, Trivia, TriviaKind},
+};
+
+//Walks the input once. position and read_position are byte offsets of the
+//current and the next character, so every step decodes a single UTF-8
+//character instead of searching the input from the start.
 #[derive(Debug)]
-pub struct Lexer {
-    input: String,
+pub struct Lexer<'src> {
+    input: &'src str,
+    //Byte offset of input within the whole source, added to every span
+    offset: usize,
     position: usize,
     read_position: usize,
+    //char::MAX once the input is exhausted, check is_at_end() to tell it apart
+    //from a literal U+10FFFF in the source
     ch: char,
+    line: usize,
+    column: usize,
+    errors: Vec<Diagnostic>,
+    //Set once the iterator has handed out Eof
+    finished: bool,
+    keep_trivia: bool,
+    //Where lexing the last token began, in front of its leading trivia
+    token_start: Span,
 }
 
-impl Lexer {
-    pub fn new(input: &str) -> Self {
+impl<'src> Lexer<'src> {
+    pub fn new(input: &'src str) -> Self {
+        Lexer::with_position(input, 0, 1, 1)
+    }
+
+    //Lexes input as if it started at the given byte offset, line and column of
+    //a larger source, used to lex a source piece by piece
+    pub fn with_position(input: &'src str, offset: usize, line: usize, column: usize) -> Self {
         let mut lexer = Lexer {
-            input: input.to_string(),
+            input,
+            offset,
             position: 0,
             read_position: 0,
             ch: char::MAX,
+            line,
+            //read_char moves onto the first character
+            column: column - 1,
+            errors: vec![],
+            finished: false,
+            keep_trivia: false,
+            token_start: Span::new(offset, offset, line, column),
         };
 
         lexer.read_char();
         lexer
     }
 
-    pub fn next_token(&mut self) -> Token {
-        self.skip_whitespaces();
+    //Attaches whitespace and comments to the tokens. Trailing trivia runs up
+    //to and including the end of the line, everything after it leads the next
+    //token, so concatenating all trivia and token source gives back the input.
+    pub fn with_trivia(mut self) -> Self {
+        self.keep_trivia = true;
+        self
+    }
 
-        let token_char = self.ch;
-        let char_string = token_char.to_string();
-        let token = match token_char {
-            '=' => {
-                if self.peek_char() == '=' {
-                    let tmp_char = self.ch;
-                    self.read_char();
-                    Token {
-                        token_type: TokenType::Equals,
-                        literal: tmp_char.to_string() + self.ch.to_string().as_str(),
-                    }
-                } else {
-                    Token {
-                        token_type: TokenType::Assign,
-                        literal: char_string,
-                    }
-                }
+    pub fn token_start(&self) -> Span {
+        self.token_start
+    }
+
+    pub fn input(&self) -> &'src str {
+        self.input
+    }
+
+    pub fn errors(&self) -> &[Diagnostic] {
+        &self.errors
+    }
+
+    //Hands out the errors found so far, the lexer keeps going after reporting
+    //them so every token is still produced
+    pub fn take_errors(&mut self) -> Vec<Diagnostic> {
+        std::mem::take(&mut self.errors)
+    }
+
+    pub fn next_token(&mut self) -> Token<'src> {
+        self.token_start = self.span(self.position, self.position, self.line, self.column);
+        let leading_trivia = self.read_trivia(false);
+
+        let start = self.position;
+        let line = self.line;
+        let column = self.column;
+
+        let mut literal = None;
+        let token_type = match self.ch {
+            _ if self.is_at_end() => TokenType::Eof,
+            '=' => self.read_operator(TokenType::Assign, '=', TokenType::Equals),
+            '!' => self.read_operator(TokenType::Bang, '=', TokenType::NotEquals),
+            ';' => self.read_single(TokenType::Semicolon),
+            ':' => self.read_single(TokenType::Colon),
+            '(' => self.read_single(TokenType::LParen),
+            ')' => self.read_single(TokenType::RParen),
+            ',' => self.read_single(TokenType::Comma),
+            '+' => self.read_operator(TokenType::Plus, '=', TokenType::PlusAssign),
+            '{' => self.read_single(TokenType::LBrace),
+            '}' => self.read_single(TokenType::RBrace),
+            '[' => self.read_single(TokenType::LBracket),
+            ']' => self.read_single(TokenType::RBracket),
+            '-' => self.read_operator(TokenType::Minus, '=', TokenType::MinusAssign),
+            '/' => self.read_operator(TokenType::Slash, '=', TokenType::SlashAssign),
+            '*' if self.peek_char() == '*' => {
+                self.read_operator(TokenType::Asterisk, '*', TokenType::Power)
             }
-            ';' => Token {
-                token_type: TokenType::Semicolon,
-                literal: char_string,
-            },
-            '(' => Token {
-                token_type: TokenType::LParen,
-                literal: char_string,
-            },
-            ')' => Token {
-                token_type: TokenType::RParen,
-                literal: char_string,
-            },
-            ',' => Token {
-                token_type: TokenType::Comma,
-                literal: char_string,
-            },
-            '+' => Token {
-                token_type: TokenType::Plus,
-                literal: char_string,
-            },
-            '{' => Token {
-                token_type: TokenType::LBrace,
-                literal: char_string,
-            },
-            '}' => Token {
-                token_type: TokenType::RBrace,
-                literal: char_string,
-            },
-            '-' => Token {
-                token_type: TokenType::Minus,
-                literal: char_string,
-            },
-            '/' => Token {
-                token_type: TokenType::Slash,
-                literal: char_string,
-            },
-            '!' => {
-                if self.peek_char() == '=' {
-                    let tmp_char = self.ch;
-                    self.read_char();
-                    Token {
-                        token_type: TokenType::NotEquals,
-                        literal: tmp_char.to_string() + self.ch.to_string().as_str(),
-                    }
-                } else {
-                    Token {
-                        token_type: TokenType::Bang,
-                        literal: char_string,
-                    }
-                }
+            '*' => self.read_operator(TokenType::Asterisk, '=', TokenType::AsteriskAssign),
+            '%' => self.read_single(TokenType::Percent),
+            '<' if self.peek_char() == '<' => {
+                self.read_operator(TokenType::Lt, '<', TokenType::ShiftLeft)
+            }
+            '<' => self.read_operator(TokenType::Lt, '=', TokenType::LtEquals),
+            '>' if self.peek_char() == '>' => {
+                self.read_operator(TokenType::Gt, '>', TokenType::ShiftRight)
+            }
+            '>' => self.read_operator(TokenType::Gt, '=', TokenType::GtEquals),
+            '&' => self.read_operator(TokenType::Ampersand, '&', TokenType::And),
+            '|' => self.read_operator(TokenType::Pipe, '|', TokenType::Or),
+            '^' => self.read_single(TokenType::Caret),
+            '~' => self.read_single(TokenType::Tilde),
+            '"' => {
+                literal = Some(self.read_string());
+                TokenType::String
             }
-            '*' => Token {
-                token_type: TokenType::Asterisk,
-                literal: char_string,
-            },
-            '<' => Token {
-                token_type: TokenType::Lt,
-                literal: char_string,
-            },
-            '>' => Token {
-                token_type: TokenType::Gt,
-                literal: char_string,
-            },
-            char::MAX => Token {
-                token_type: TokenType::Eof,
-                literal: "".to_string(),
-            },
-            _ => {
-                if is_letter(token_char) {
-                    return self.read_identifier();
-                } else if is_digit(token_char) {
-                    return Token {
-                        token_type: TokenType::Int,
-                        literal: self.read_number(),
-                    };
+            ch => {
+                if is_letter(ch) {
+                    self.read_identifier()
+                } else if is_digit(ch) {
+                    self.read_number()
                 } else {
-                    return Token {
-                        token_type: TokenType::Illegal,
-                        literal: char_string,
-                    };
+                    self.read_single(TokenType::Illegal)
                 }
             }
         };
 
-        self.read_char();
+        let literal = literal.unwrap_or(Cow::Borrowed(&self.input[start..self.position]));
+        let span = self.span(start, self.position, line, column);
+        //Without trivia the whitespace is left for the next token to skip, so
+        //a token never reads past its own end
+        let trailing_trivia = match token_type {
+            TokenType::Eof => vec![],
+            _ if !self.keep_trivia => vec![],
+            _ => self.read_trivia(true),
+        };
 
-        token
+        Token {
+            token_type,
+            literal,
+            span,
+            leading_trivia,
+            trailing_trivia,
+        }
     }
 
     fn read_char(&mut self) {
-        if self.read_position >= self.input.len() {
-            self.ch = char::MAX;
+        if self.ch == '\n' {
+            self.line += 1;
+            self.column = 1;
         } else {
-            self.ch = self
-                .input
-                .char_indices()
-                .find(|(index, _c)| index == &self.read_position)
-                .map(|x| x.1)
-                .unwrap_or(char::MAX);
+            self.column += 1;
         }
 
         self.position = self.read_position;
-        self.read_position += 1;
+        match self.input[self.read_position..].chars().next() {
+            Some(ch) => {
+                self.ch = ch;
+                self.read_position += ch.len_utf8();
+            }
+            None => self.ch = char::MAX,
+        }
+    }
+
+    fn is_at_end(&self) -> bool {
+        self.position >= self.input.len()
+    }
+
+    fn span(&self, start: usize, end: usize, line: usize, column: usize) -> Span {
+        Span::new(self.offset + start, self.offset + end, line, column)
+    }
+
+    fn read_single(&mut self, token_type: TokenType) -> TokenType {
+        self.read_char();
+        token_type
+    }
+
+    //Reads either a one character operator or, if the next character is second,
+    //the two character operator combined
+    fn read_operator(&mut self, single: TokenType, second: char, combined: TokenType) -> TokenType {
+        if self.peek_char() != second {
+            return self.read_single(single);
+        }
+
+        self.read_char();
+        self.read_char();
+        combined
     }
 
-    fn read_identifier(&mut self) -> Token {
+    fn read_identifier(&mut self) -> TokenType {
         let position = self.position;
         while is_letter(self.ch) {
             self.read_char();
@@ -155,7 +209,7 @@ impl Lexer {
 
         let literal = &self.input[position..self.position];
 
-        let token_type = match literal {
+        match literal {
             "fn" => TokenType::Function,
             "let" => TokenType::Let,
             "return" => TokenType::Return,
@@ -164,48 +218,298 @@ impl Lexer {
             "if" => TokenType::If,
             "else" => TokenType::Else,
             _ => TokenType::Ident,
+        }
+    }
+
+    //Reads the string starting at the current `"` and returns its content with
+    //all escape sequences resolved. The content is only copied once the first
+    //escape sequence shows up.
+    fn read_string(&mut self) -> Cow<'src, str> {
+        let quote = self.span(self.position, self.position + 1, self.line, self.column);
+        let content_start = self.position + 1;
+        let mut owned: Option<String> = None;
+        let mut content_end;
+
+        loop {
+            self.read_char();
+            content_end = self.position;
+            match self.ch {
+                _ if self.is_at_end() => {
+                    self.errors.push(
+                        Diagnostic::error("Unterminated string literal", quote)
+                            .with_code("E0101")
+                            .with_label("string starts here")
+                            .with_help("add a closing `\"`"),
+                    );
+                    break;
+                }
+                '"' => {
+                    self.read_char();
+                    break;
+                }
+                '\\' => {
+                    let value = owned.get_or_insert_with(|| {
+                        self.input[content_start..self.position].to_string()
+                    });
+                    if let Some(ch) = self.read_escape() {
+                        value.push(ch);
+                    }
+                }
+                ch => {
+                    if let Some(value) = owned.as_mut() {
+                        value.push(ch);
+                    }
+                }
+            }
+        }
+
+        match owned {
+            Some(value) => Cow::Owned(value),
+            None => Cow::Borrowed(&self.input[content_start..content_end]),
+        }
+    }
+
+    //Called on the backslash, leaves the lexer on the last character of the
+    //escape sequence
+    fn read_escape(&mut self) -> Option<char> {
+        let start = self.position;
+        let line = self.line;
+        let column = self.column;
+
+        self.read_char();
+        let escaped = match self.ch {
+            //Unterminated, reported by read_string
+            _ if self.is_at_end() => return None,
+            'n' => '\n',
+            't' => '\t',
+            '"' => '"',
+            '\\' => '\\',
+            'u' => return self.read_unicode_escape(start, line, column),
+            ch => {
+                let span = self.span(start, self.position + ch.len_utf8(), line, column);
+                self.errors.push(
+                    Diagnostic::error(&format!("Unknown escape sequence \\{}", ch), span)
+                        .with_code("E0102")
+                        .with_note("supported escapes are \\n, \\t, \\\", \\\\ and \\u{...}"),
+                );
+                ch
+            }
         };
 
-        Token {
-            token_type,
-            literal: literal.to_string(),
+        Some(escaped)
+    }
+
+    fn read_unicode_escape(&mut self, start: usize, line: usize, column: usize) -> Option<char> {
+        let mut digits = String::new();
+        let mut closed = false;
+
+        if self.peek_char() == '{' {
+            self.read_char();
+            while self.peek_char().is_ascii_hexdigit() && digits.len() < 6 {
+                self.read_char();
+                digits.push(self.ch);
+            }
+            if self.peek_char() == '}' {
+                self.read_char();
+                closed = true;
+            }
+        }
+
+        let value = u32::from_str_radix(&digits, 16)
+            .ok()
+            .and_then(char::from_u32);
+        if closed && value.is_some() {
+            return value;
         }
+
+        let span = self.span(start, self.position + self.ch.len_utf8(), line, column);
+        self.errors.push(
+            Diagnostic::error("Invalid unicode escape", span)
+                .with_code("E0103")
+                .with_help("write unicode escapes as \\u{1F600} with 1 to 6 hex digits"),
+        );
+        None
     }
 
-    fn read_number(&mut self) -> String {
-        let position = self.position;
-        while is_digit(self.ch) {
+    //Reads `12`, `1.5`, `1e9` or `2.5E-3`. A `.` only belongs to the number
+    //if a digit follows it. Malformed exponents like `1e+` are still read as a
+    //whole and reported by the parser.
+    fn read_number(&mut self) -> TokenType {
+        let mut token_type = TokenType::Int;
+
+        //`0x1F`, `0o17` and `0b1010`. Everything that could belong to the
+        //literal is read, so `0b102` is one token the parser can point into.
+        if self.ch == '0' && matches!(self.peek_char(), 'x' | 'X' | 'o' | 'O' | 'b' | 'B') {
             self.read_char();
+            self.read_char();
+            while self.ch.is_ascii_alphanumeric() || self.ch == '_' {
+                self.read_char();
+            }
+            return token_type;
         }
 
-        self.input[position..self.position].to_string()
+        self.read_digits();
+
+        if self.ch == '.' && is_digit(self.peek_char()) {
+            token_type = TokenType::Float;
+            self.read_char();
+            self.read_digits();
+        }
+
+        if self.ch == 'e' || self.ch == 'E' {
+            token_type = TokenType::Float;
+            self.read_char();
+            if self.ch == '+' || self.ch == '-' {
+                self.read_char();
+            }
+            self.read_digits();
+        }
+
+        token_type
+    }
+
+    //Includes `_` separators, which are checked by the parser
+    fn read_digits(&mut self) {
+        while is_digit(self.ch) || self.ch == '_' {
+            self.read_char();
+        }
     }
 
     fn peek_char(&self) -> char {
-        if self.read_position >= self.input.len() {
-            char::MAX
-        } else {
-            self.input
-                .char_indices()
-                .find(|x| x.0 == self.read_position)
-                .map(|x| x.1)
-                .unwrap_or(char::MAX)
+        self.input[self.read_position..]
+            .chars()
+            .next()
+            .unwrap_or(char::MAX)
+    }
+
+    //Skips whitespace and comments, returning them if trivia is kept. Trailing
+    //trivia stops after the first line break.
+    fn read_trivia(&mut self, trailing: bool) -> Vec<Trivia<'src>> {
+        let mut trivia = vec![];
+
+        loop {
+            let start = self.position;
+            let line = self.line;
+            let column = self.column;
+
+            let kind = match self.ch {
+                _ if self.is_at_end() => break,
+                ' ' | '\t' => {
+                    while self.ch == ' ' || self.ch == '\t' {
+                        self.read_char();
+                    }
+                    TriviaKind::Whitespace
+                }
+                '\n' => {
+                    self.read_char();
+                    TriviaKind::Newline
+                }
+                '\r' if self.peek_char() == '\n' => {
+                    self.read_char();
+                    self.read_char();
+                    TriviaKind::Newline
+                }
+                '\r' => {
+                    self.read_char();
+                    TriviaKind::Whitespace
+                }
+                '/' if self.peek_char() == '/' => {
+                    self.skip_line_comment();
+                    TriviaKind::LineComment
+                }
+                '/' if self.peek_char() == '*' => {
+                    self.skip_block_comment();
+                    TriviaKind::BlockComment
+                }
+                _ => break,
+            };
+
+            if self.keep_trivia {
+                trivia.push(Trivia {
+                    kind,
+                    text: Cow::Borrowed(&self.input[start..self.position]),
+                    span: self.span(start, self.position, line, column),
+                });
+            }
+
+            if trailing && kind == TriviaKind::Newline {
+                break;
+            }
         }
+
+        trivia
     }
 
-    fn skip_whitespaces(&mut self) {
-        while self.ch == ' ' || self.ch == '\t' || self.ch == '\n' || self.ch == '\r' {
+    //Leaves the line break after the comment in place
+    fn skip_line_comment(&mut self) {
+        while !self.is_at_end() && self.ch != '\n' && !(self.ch == '\r' && self.peek_char() == '\n')
+        {
             self.read_char();
         }
     }
+
+    //Block comments nest, so `/* a /* b */ c */` is a single comment
+    fn skip_block_comment(&mut self) {
+        let opening = self.span(self.position, self.position + 2, self.line, self.column);
+        let mut depth = 0;
+
+        loop {
+            match self.ch {
+                _ if self.is_at_end() => {
+                    self.errors.push(
+                        Diagnostic::error("Unterminated block comment", opening)
+                            .with_code("E0104")
+                            .with_label("comment starts here")
+                            .with_help("add a closing `*/`"),
+                    );
+                    return;
+                }
+                '/' if self.peek_char() == '*' => {
+                    depth += 1;
+                    self.read_char();
+                    self.read_char();
+                }
+                '*' if self.peek_char() == '/' => {
+                    depth -= 1;
+                    self.read_char();
+                    self.read_char();
+                    if depth == 0 {
+                        return;
+                    }
+                }
+                _ => self.read_char(),
+            }
+        }
+    }
+}
+
+//Yields every token up to and including Eof
+impl<'src> Iterator for Lexer<'src> {
+    type Item = Token<'src>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        let token = self.next_token();
+        self.finished = token.token_type == TokenType::Eof;
+        Some(token)
+    }
+}
+
+//Lexes the whole input, the last token is always Eof. Errors are dropped, use
+//a Lexer directly to get them.
+pub fn tokenize(input: &str) -> Vec<Token<'_>> {
+    Lexer::new(input).collect()
 }
 
 fn is_letter(ch: char) -> bool {
-    ('a'..='z').contains(&ch) || ('A'..='Z').contains(&ch) || ch == '_'
+    ch.is_ascii_lowercase() || ch.is_ascii_uppercase() || ch == '_'
 }
 
 fn is_digit(ch: char) -> bool {
-    ('0'..='9').contains(&ch) && ch != ';'
+    ch.is_ascii_digit()
 }
 
 #[cfg(test)]
@@ -216,7 +520,7 @@ mod tests {
     #[test]
     fn next_token_test() {
         let string_to_parse = "=+(){},;";
-        let expected = vec![
+        let expected = [
             TokenType::Assign,
             TokenType::Plus,
             TokenType::LParen,
@@ -244,7 +548,7 @@ mod tests {
         };
         
         let result = add(five, ten);
-        !-/*5;
+        !-/ *5;
         5 < 10 > 5;
 
         if (5 < 10) {
@@ -337,7 +641,513 @@ mod tests {
 
         for x in expected.iter() {
             let token = lexer.next_token();
-            assert_eq!(x, &(token.token_type, token.literal.as_str()))
+            assert_eq!(x, &(token.token_type, token.literal.as_ref()))
+        }
+    }
+
+    #[test]
+    fn token_span_test() {
+        let input = "let x = 10;\n  x != 5\n";
+        let expected = [
+            (TokenType::Let, 0, 3, 1, 1),
+            (TokenType::Ident, 4, 5, 1, 5),
+            (TokenType::Assign, 6, 7, 1, 7),
+            (TokenType::Int, 8, 10, 1, 9),
+            (TokenType::Semicolon, 10, 11, 1, 11),
+            (TokenType::Ident, 14, 15, 2, 3),
+            (TokenType::NotEquals, 16, 18, 2, 5),
+            (TokenType::Int, 19, 20, 2, 8),
+            (TokenType::Eof, 21, 21, 3, 1),
+        ];
+
+        let mut lexer = Lexer::new(input);
+
+        for x in expected.iter() {
+            let token = lexer.next_token();
+            assert_eq!(
+                x,
+                &(
+                    token.token_type,
+                    token.span.start,
+                    token.span.end,
+                    token.span.line,
+                    token.span.column
+                )
+            );
+        }
+    }
+
+    #[test]
+    fn assignment_token_test() {
+        let string_to_parse = "x = 1; x += 2; x -= 3; x *= 4; x /= 5; x == y";
+        let expected = [
+            (TokenType::Ident, "x"),
+            (TokenType::Assign, "="),
+            (TokenType::Int, "1"),
+            (TokenType::Semicolon, ";"),
+            (TokenType::Ident, "x"),
+            (TokenType::PlusAssign, "+="),
+            (TokenType::Int, "2"),
+            (TokenType::Semicolon, ";"),
+            (TokenType::Ident, "x"),
+            (TokenType::MinusAssign, "-="),
+            (TokenType::Int, "3"),
+            (TokenType::Semicolon, ";"),
+            (TokenType::Ident, "x"),
+            (TokenType::AsteriskAssign, "*="),
+            (TokenType::Int, "4"),
+            (TokenType::Semicolon, ";"),
+            (TokenType::Ident, "x"),
+            (TokenType::SlashAssign, "/="),
+            (TokenType::Int, "5"),
+            (TokenType::Semicolon, ";"),
+            (TokenType::Ident, "x"),
+            (TokenType::Equals, "=="),
+            (TokenType::Ident, "y"),
+            (TokenType::Eof, ""),
+        ];
+
+        let mut lexer = Lexer::new(string_to_parse);
+
+        for x in expected.iter() {
+            let token = lexer.next_token();
+            assert_eq!(x, &(token.token_type, token.literal.as_ref()))
+        }
+    }
+
+    #[test]
+    fn string_token_test() {
+        let string_to_parse =
+            r#""foobar" "foo bar" "" "a\nb\t\"c\"\\" "\u{48}\u{1F600}" "héllo wörld" x"#;
+        let expected = [
+            (TokenType::String, "foobar"),
+            (TokenType::String, "foo bar"),
+            (TokenType::String, ""),
+            (TokenType::String, "a\nb\t\"c\"\\"),
+            (TokenType::String, "H😀"),
+            (TokenType::String, "héllo wörld"),
+            (TokenType::Ident, "x"),
+            (TokenType::Eof, ""),
+        ];
+
+        let mut lexer = Lexer::new(string_to_parse);
+
+        for x in expected.iter() {
+            let token = lexer.next_token();
+            assert_eq!(x, &(token.token_type, token.literal.as_ref()))
+        }
+        assert!(lexer.errors().is_empty());
+    }
+
+    #[test]
+    fn string_span_test() {
+        let input = "let s = \"ä\\n\";\ns";
+        let mut lexer = Lexer::new(input);
+
+        let tokens: Vec<Token> = (0..6).map(|_| lexer.next_token()).collect();
+        assert_eq!(TokenType::String, tokens[3].token_type);
+        assert_eq!("\"ä\\n\"", &input[tokens[3].span.start..tokens[3].span.end]);
+        assert_eq!(TokenType::Ident, tokens[5].token_type);
+        assert_eq!((2, 1), (tokens[5].span.line, tokens[5].span.column));
+    }
+
+    #[test]
+    fn string_errors_test() {
+        let test_inputs = [
+            ("let s = \"abc", "Unterminated string literal", 1, 9),
+            ("\"ok\";\n  \"a\\qb\"", "Unknown escape sequence \\q", 2, 5),
+            ("\"\\u{110000}\"", "Invalid unicode escape", 1, 2),
+            ("\"\\u{41\"", "Invalid unicode escape", 1, 2),
+            ("\"\\u41\"", "Invalid unicode escape", 1, 2),
+        ];
+
+        for input in test_inputs.iter() {
+            let mut lexer = Lexer::new(input.0);
+            while lexer.next_token().token_type != TokenType::Eof {}
+
+            assert_eq!(1, lexer.errors().len(), "{}", input.0);
+            let error = &lexer.errors()[0];
+            assert_eq!(input.1, error.message);
+            assert_eq!((input.2, input.3), (error.span().line, error.span().column));
+        }
+    }
+
+    #[test]
+    fn bracket_token_test() {
+        let string_to_parse = "[1, 2][0:-1]";
+        let expected = [
+            (TokenType::LBracket, "["),
+            (TokenType::Int, "1"),
+            (TokenType::Comma, ","),
+            (TokenType::Int, "2"),
+            (TokenType::RBracket, "]"),
+            (TokenType::LBracket, "["),
+            (TokenType::Int, "0"),
+            (TokenType::Colon, ":"),
+            (TokenType::Minus, "-"),
+            (TokenType::Int, "1"),
+            (TokenType::RBracket, "]"),
+            (TokenType::Eof, ""),
+        ];
+
+        let mut lexer = Lexer::new(string_to_parse);
+
+        for x in expected.iter() {
+            let token = lexer.next_token();
+            assert_eq!(x, &(token.token_type, token.literal.as_ref()))
         }
     }
+
+    #[test]
+    fn utf8_input_test() {
+        let input = "let s = \"日本\"; ü + é\n\u{10FFFF} x";
+        let expected = [
+            (TokenType::Let, "let", 1, 1),
+            (TokenType::Ident, "s", 1, 5),
+            (TokenType::Assign, "=", 1, 7),
+            (TokenType::String, "日本", 1, 9),
+            (TokenType::Semicolon, ";", 1, 13),
+            (TokenType::Illegal, "ü", 1, 15),
+            (TokenType::Plus, "+", 1, 17),
+            (TokenType::Illegal, "é", 1, 19),
+            (TokenType::Illegal, "\u{10FFFF}", 2, 1),
+            (TokenType::Ident, "x", 2, 3),
+            (TokenType::Eof, "", 2, 4),
+        ];
+
+        let mut lexer = Lexer::new(input);
+
+        for x in expected.iter() {
+            let token = lexer.next_token();
+            assert_eq!(
+                x,
+                &(
+                    token.token_type,
+                    token.literal.as_ref(),
+                    token.span.line,
+                    token.span.column
+                )
+            );
+            assert_eq!(
+                token.token_type != TokenType::String,
+                token.literal == input[token.span.start..token.span.end]
+            );
+        }
+    }
+
+    #[test]
+    fn borrowed_literal_test() {
+        let input = r#"let name = "plain"; "esc\tape" + 10"#;
+        let expected = [
+            ("let", true),
+            ("name", true),
+            ("=", true),
+            ("plain", true),
+            (";", true),
+            ("esc\tape", false),
+            ("+", true),
+            ("10", true),
+        ];
+
+        let mut lexer = Lexer::new(input);
+
+        for x in expected.iter() {
+            let token = lexer.next_token();
+            assert_eq!(x.0, token.literal, "{}", x.0);
+            assert_eq!(
+                x.1,
+                matches!(token.literal, Cow::Borrowed(_)),
+                "{} borrowed",
+                x.0
+            );
+        }
+    }
+
+    #[test]
+    fn logical_and_comparison_token_test() {
+        let input = "a <= b >= c < d > e && f || g % h ** i * j & | **=";
+        let expected = [
+            (TokenType::Ident, "a"),
+            (TokenType::LtEquals, "<="),
+            (TokenType::Ident, "b"),
+            (TokenType::GtEquals, ">="),
+            (TokenType::Ident, "c"),
+            (TokenType::Lt, "<"),
+            (TokenType::Ident, "d"),
+            (TokenType::Gt, ">"),
+            (TokenType::Ident, "e"),
+            (TokenType::And, "&&"),
+            (TokenType::Ident, "f"),
+            (TokenType::Or, "||"),
+            (TokenType::Ident, "g"),
+            (TokenType::Percent, "%"),
+            (TokenType::Ident, "h"),
+            (TokenType::Power, "**"),
+            (TokenType::Ident, "i"),
+            (TokenType::Asterisk, "*"),
+            (TokenType::Ident, "j"),
+            (TokenType::Ampersand, "&"),
+            (TokenType::Pipe, "|"),
+            (TokenType::Power, "**"),
+            (TokenType::Assign, "="),
+            (TokenType::Eof, ""),
+        ];
+
+        let mut lexer = Lexer::new(input);
+
+        for x in expected.iter() {
+            let token = lexer.next_token();
+            assert_eq!(x, &(token.token_type, token.literal.as_ref()));
+        }
+    }
+
+    #[test]
+    fn bitwise_token_test() {
+        let input = "a & b | c ^ ~d << 2 >> 1 <<= >>= &&& |||";
+        let expected = [
+            (TokenType::Ident, "a"),
+            (TokenType::Ampersand, "&"),
+            (TokenType::Ident, "b"),
+            (TokenType::Pipe, "|"),
+            (TokenType::Ident, "c"),
+            (TokenType::Caret, "^"),
+            (TokenType::Tilde, "~"),
+            (TokenType::Ident, "d"),
+            (TokenType::ShiftLeft, "<<"),
+            (TokenType::Int, "2"),
+            (TokenType::ShiftRight, ">>"),
+            (TokenType::Int, "1"),
+            (TokenType::ShiftLeft, "<<"),
+            (TokenType::Assign, "="),
+            (TokenType::ShiftRight, ">>"),
+            (TokenType::Assign, "="),
+            (TokenType::And, "&&"),
+            (TokenType::Ampersand, "&"),
+            (TokenType::Or, "||"),
+            (TokenType::Pipe, "|"),
+            (TokenType::Eof, ""),
+        ];
+
+        let mut lexer = Lexer::new(input);
+
+        for x in expected.iter() {
+            let token = lexer.next_token();
+            assert_eq!(x, &(token.token_type, token.literal.as_ref()));
+        }
+    }
+
+    #[test]
+    fn float_token_test() {
+        let input = "3.14 1e9 2.5E-3 7e+2 10 1.x [1][0].5";
+        let expected = [
+            (TokenType::Float, "3.14"),
+            (TokenType::Float, "1e9"),
+            (TokenType::Float, "2.5E-3"),
+            (TokenType::Float, "7e+2"),
+            (TokenType::Int, "10"),
+            (TokenType::Int, "1"),
+            (TokenType::Illegal, "."),
+            (TokenType::Ident, "x"),
+            (TokenType::LBracket, "["),
+            (TokenType::Int, "1"),
+            (TokenType::RBracket, "]"),
+            (TokenType::LBracket, "["),
+            (TokenType::Int, "0"),
+            (TokenType::RBracket, "]"),
+            (TokenType::Illegal, "."),
+            (TokenType::Int, "5"),
+            (TokenType::Eof, ""),
+        ];
+
+        let mut lexer = Lexer::new(input);
+
+        for x in expected.iter() {
+            let token = lexer.next_token();
+            assert_eq!(x, &(token.token_type, token.literal.as_ref()));
+        }
+        assert!(lexer.errors().is_empty());
+    }
+
+    #[test]
+    fn radix_and_separator_token_test() {
+        let input = "0x1F 0o17 0b1010 0XfF 1_000_000 1_000.5e1_0 0x 0b102 0x_ 7_ 0 b";
+        let expected = [
+            (TokenType::Int, "0x1F"),
+            (TokenType::Int, "0o17"),
+            (TokenType::Int, "0b1010"),
+            (TokenType::Int, "0XfF"),
+            (TokenType::Int, "1_000_000"),
+            (TokenType::Float, "1_000.5e1_0"),
+            (TokenType::Int, "0x"),
+            (TokenType::Int, "0b102"),
+            (TokenType::Int, "0x_"),
+            (TokenType::Int, "7_"),
+            (TokenType::Int, "0"),
+            (TokenType::Ident, "b"),
+            (TokenType::Eof, ""),
+        ];
+
+        let mut lexer = Lexer::new(input);
+
+        for x in expected.iter() {
+            let token = lexer.next_token();
+            assert_eq!(x, &(token.token_type, token.literal.as_ref()));
+        }
+    }
+
+    #[test]
+    fn comment_test() {
+        let input = "let x = 5; // five
+        /* outer /* nested */ still comment */ x / 2 /= 3
+        //last";
+        let expected = [
+            (TokenType::Let, "let"),
+            (TokenType::Ident, "x"),
+            (TokenType::Assign, "="),
+            (TokenType::Int, "5"),
+            (TokenType::Semicolon, ";"),
+            (TokenType::Ident, "x"),
+            (TokenType::Slash, "/"),
+            (TokenType::Int, "2"),
+            (TokenType::SlashAssign, "/="),
+            (TokenType::Int, "3"),
+            (TokenType::Eof, ""),
+        ];
+
+        let mut lexer = Lexer::new(input);
+
+        for x in expected.iter() {
+            let token = lexer.next_token();
+            assert_eq!(x, &(token.token_type, token.literal.as_ref()));
+            assert!(token.leading_trivia.is_empty());
+        }
+        assert!(lexer.errors().is_empty());
+    }
+
+    #[test]
+    fn unterminated_block_comment_test() {
+        let mut lexer = Lexer::new("1 /* a /* b */");
+
+        assert_eq!(TokenType::Int, lexer.next_token().token_type);
+        assert_eq!(TokenType::Eof, lexer.next_token().token_type);
+
+        let errors = lexer.take_errors();
+        assert_eq!(1, errors.len());
+        assert_eq!(Some("E0104".to_string()), errors[0].code);
+        assert_eq!(Span::new(2, 4, 1, 3), errors[0].span());
+    }
+
+    #[test]
+    fn trivia_test() {
+        let input = "let x = 1; // one\r\n\n  /* doc */ x\t/*a*/+ \"\\n\"\n// end\n";
+        let lexer = Lexer::new(input).with_trivia();
+
+        let tokens: Vec<Token> = lexer.collect();
+
+        let semicolon = &tokens[4];
+        let trailing: Vec<(TriviaKind, &str)> = semicolon
+            .trailing_trivia
+            .iter()
+            .map(|t| (t.kind, t.text.as_ref()))
+            .collect();
+        assert_eq!(
+            vec![
+                (TriviaKind::Whitespace, " "),
+                (TriviaKind::LineComment, "// one"),
+                (TriviaKind::Newline, "\r\n"),
+            ],
+            trailing
+        );
+
+        let x = &tokens[5];
+        let leading: Vec<(TriviaKind, &str)> = x
+            .leading_trivia
+            .iter()
+            .map(|t| (t.kind, t.text.as_ref()))
+            .collect();
+        assert_eq!(
+            vec![
+                (TriviaKind::Newline, "\n"),
+                (TriviaKind::Whitespace, "  "),
+                (TriviaKind::BlockComment, "/* doc */"),
+                (TriviaKind::Whitespace, " "),
+            ],
+            leading
+        );
+        assert_eq!(Span::new(22, 31, 3, 3), x.leading_trivia[2].span);
+
+        //Everything is kept, so the source can be rebuilt from the tokens
+        let mut source = String::new();
+        for token in tokens.iter() {
+            for trivia in token.leading_trivia.iter() {
+                source.push_str(&trivia.text);
+            }
+            source.push_str(&input[token.span.start..token.span.end]);
+            for trivia in token.trailing_trivia.iter() {
+                source.push_str(&trivia.text);
+            }
+        }
+        assert_eq!(input, source);
+    }
+
+    #[test]
+    fn iterator_test() {
+        let mut lexer = Lexer::new("let x");
+
+        let tokens: Vec<TokenType> = lexer.by_ref().map(|t| t.token_type).collect();
+        assert_eq!(
+            vec![TokenType::Let, TokenType::Ident, TokenType::Eof],
+            tokens
+        );
+        assert_eq!(None, lexer.next());
+
+        let tokens = tokenize("");
+        assert_eq!(1, tokens.len());
+        assert_eq!(TokenType::Eof, tokens[0].token_type);
+    }
+
+    #[test]
+    fn with_position_test() {
+        let mut lexer = Lexer::with_position("x\n  \"a", 10, 3, 5);
+
+        let token = lexer.next_token();
+        assert_eq!(Span::new(10, 11, 3, 5), token.span);
+
+        let token = lexer.next_token();
+        assert_eq!(Span::new(14, 16, 4, 3), token.span);
+        assert_eq!(Span::new(14, 15, 4, 3), lexer.errors()[0].span());
+    }
+
+    //Run with `cargo test --release -- --ignored --nocapture` to see the numbers
+    #[test]
+    #[ignore]
+    fn lexer_scales_linearly_benchmark() {
+        let chunk = "let add = fn(x, y) { x + y; };\nlet s = \"héllo wörld\";\nif (add(1, 2) != 3) { return [1, 2][0]; }\n";
+        let mut timings = vec![];
+
+        for megabytes in [1, 2, 4, 8] {
+            let input = chunk.repeat(megabytes * 1024 * 1024 / chunk.len());
+            let start = std::time::Instant::now();
+
+            let mut lexer = Lexer::new(&input);
+            let mut tokens = 0;
+            while lexer.next_token().token_type != TokenType::Eof {
+                tokens += 1;
+            }
+
+            let elapsed = start.elapsed();
+            println!(
+                "{} MB: {} tokens in {:?} ({:.1} MB/s)",
+                megabytes,
+                tokens,
+                elapsed,
+                input.len() as f64 / 1024.0 / 1024.0 / elapsed.as_secs_f64()
+            );
+            timings.push(elapsed.as_secs_f64());
+        }
+
+        //8x the input should take about 8x the time, quadratic lexing would be 64x
+        let ratio = timings[3] / timings[0];
+        assert!(ratio < 16.0, "lexing time grew {:.1}x for 8x input", ratio);
+    }
 }
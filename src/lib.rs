@@ -0,0 +1,76 @@
+//! Lexer, parser and evaluator of the Monkey language.
+//!
+//! Only the items re-exported here are public, the module layout is an
+//! implementation detail. Running a programm goes through parse and evaluate:
+//!
+//! ```
+//! # use std::{cell::RefCell, rc::Rc};
+//! # use interpreter_go::{evaluate, parse, Environment, Object};
+//! let env = Rc::new(RefCell::new(Environment::new()));
+//! let programm = parse("let x = 1 + 2; x * 3").unwrap();
+//! assert_eq!(Object::Integer(9), evaluate(&programm, &env));
+//! ```
+//!
+//! Hosts that expose their own functions or values use an Interpreter instead:
+//!
+//! ```
+//! # use interpreter_go::{Interpreter, Object};
+//! let mut interpreter = Interpreter::new();
+//! interpreter.register_fn("double", |x: i64| x * 2);
+//! assert_eq!(Object::Integer(6), interpreter.run("double(3)").unwrap());
+//! ```
+
+mod ast;
+#[cfg(feature = "bignum")]
+mod bignum;
+mod builtins;
+mod diagnostic;
+mod environment;
+mod error;
+mod eval;
+mod interpreter;
+mod lexer;
+mod limits;
+mod object;
+mod parser;
+#[cfg(feature = "serde")]
+mod serialize;
+mod span;
+mod statements;
+mod stream;
+mod token;
+mod value;
+
+pub use ast::Programm;
+#[cfg(feature = "bignum")]
+pub use bignum::BigInt;
+pub use builtins::{Builtin, BuiltinFunction};
+pub use diagnostic::{Diagnostic, Label, Severity};
+pub use environment::{Env, Environment};
+pub use error::ParsingError;
+pub use eval::eval_programm as evaluate;
+pub use interpreter::{HostFunction, Interpreter};
+pub use lexer::{tokenize, Lexer};
+pub use limits::{
+    LimitError, Limits, DEFAULT_MAX_CALL_DEPTH, DEFAULT_MAX_EXPRESSION_DEPTH,
+    DEFAULT_MAX_INTEGER_SIZE, DEFAULT_MAX_VALUE_DEPTH,
+};
+pub use object::{Function, HashKey, HashObject, Object};
+pub use parser::Parser;
+pub use span::Span;
+pub use statements::{BlockStatement, Expressions, FunctionCache, Identifier, Node, Statements};
+pub use stream::StreamLexer;
+pub use token::{Token, TokenType, Trivia, TriviaKind};
+pub use value::{FromValue, IntoValue};
+
+//Parses a whole programm. Fails with every error found, the parser recovers
+//after a broken statement and keeps going.
+pub fn parse(input: &str) -> Result<Programm<'_>, Vec<ParsingError>> {
+    let mut parser = Parser::new(Lexer::new(input));
+    let programm = parser.parse_programm();
+
+    match parser.take_errors() {
+        errors if errors.is_empty() => Ok(programm.unwrap_or_default()),
+        errors => Err(errors),
+    }
+}
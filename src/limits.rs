@@ -0,0 +1,319 @@
+use std::{
+    cell::Cell,
+    fmt::Display,
+    time::{Duration, Instant},
+};
+
+use crate::object::Object;
+
+//Deep enough for ordinary recursion while leaving room on the stack of a
+//thread with the default 2MiB
+pub const DEFAULT_MAX_CALL_DEPTH: usize = 256;
+
+//Expressions being evaluated at once, summed over all pending calls. The
+//call depth alone does not bound the stack, as every call can add a deeply
+//nested expression, this does with room for the largest frames left.
+pub const DEFAULT_MAX_EXPRESSION_DEPTH: usize = 512;
+
+//Arrays and hashes inside each other. Cloning, comparing, printing and
+//dropping a value all recurse through it, so this keeps them on the stack.
+pub const DEFAULT_MAX_VALUE_DEPTH: usize = 128;
+
+//Bytes of a single big integer. Multiplying and dividing take time growing
+//with the square of the size, at this size the slowest of them takes
+//milliseconds, which keeps the timeout from being overrun by a single step.
+pub const DEFAULT_MAX_INTEGER_SIZE: usize = 8 * 1024;
+
+//The deadline is only looked at every few steps, reading the clock on every
+//expression would dominate the cost of evaluating it
+const DEADLINE_INTERVAL: u64 = 1024;
+
+//Resources a programm may use. Everything but the call, expression and value
+//depth and the integer size is unlimited by default, the depths are what keep
+//runaway recursion from overflowing the stack.
+//
+//    let limits = Limits::new()
+//        .with_max_steps(100_000)
+//        .with_timeout(Duration::from_millis(50));
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+    pub max_steps: Option<u64>,
+    pub max_call_depth: usize,
+    pub max_expression_depth: usize,
+    pub max_value_depth: usize,
+    pub max_integer_size: usize,
+    pub max_allocation: Option<usize>,
+    pub timeout: Option<Duration>,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits::new()
+    }
+}
+
+impl Limits {
+    pub fn new() -> Self {
+        Limits {
+            max_steps: None,
+            max_call_depth: DEFAULT_MAX_CALL_DEPTH,
+            max_expression_depth: DEFAULT_MAX_EXPRESSION_DEPTH,
+            max_value_depth: DEFAULT_MAX_VALUE_DEPTH,
+            max_integer_size: DEFAULT_MAX_INTEGER_SIZE,
+            max_allocation: None,
+            timeout: None,
+        }
+    }
+
+    //Every evaluated expression is one step
+    pub fn with_max_steps(mut self, steps: u64) -> Self {
+        self.max_steps = Some(steps);
+        self
+    }
+
+    //Function calls that have not returned yet
+    pub fn with_max_call_depth(mut self, depth: usize) -> Self {
+        self.max_call_depth = depth;
+        self
+    }
+
+    //Raising it above the default needs a thread with a larger stack
+    pub fn with_max_expression_depth(mut self, depth: usize) -> Self {
+        self.max_expression_depth = depth;
+        self
+    }
+
+    //Same as the expression depth, values deeper than the default need a
+    //larger stack
+    pub fn with_max_value_depth(mut self, depth: usize) -> Self {
+        self.max_value_depth = depth;
+        self
+    }
+
+    //Only big integers can grow, without the bignum feature every integer
+    //fits in 8 bytes
+    pub fn with_max_integer_size(mut self, size: usize) -> Self {
+        self.max_integer_size = size;
+        self
+    }
+
+    //Bytes of strings and big integers plus entries of arrays and hashes,
+    //counted over everything a value holds
+    pub fn with_max_allocation(mut self, size: usize) -> Self {
+        self.max_allocation = Some(size);
+        self
+    }
+
+    //Wall-clock time for a single evaluation, counted from its start
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+}
+
+//The limit that stopped an evaluation. The programm sees it as a runtime
+//error with this message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LimitError {
+    Steps(u64),
+    CallDepth(usize),
+    ExpressionDepth(usize),
+    ValueDepth(usize),
+    IntegerSize { size: usize, max: usize },
+    Allocation { size: usize, max: usize },
+    Timeout(Duration),
+}
+
+impl Display for LimitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LimitError::Steps(max) => write!(f, "step limit exceeded: {} steps", max),
+            LimitError::CallDepth(max) => write!(f, "call depth limit exceeded: {} calls", max),
+            LimitError::ExpressionDepth(max) => {
+                write!(f, "expression depth limit exceeded: {} levels", max)
+            }
+            LimitError::ValueDepth(max) => write!(f, "value depth limit exceeded: {} levels", max),
+            LimitError::IntegerSize { size, max } => {
+                write!(
+                    f,
+                    "integer size limit exceeded: {} bytes over {}",
+                    size, max
+                )
+            }
+            LimitError::Allocation { size, max } => {
+                write!(f, "allocation limit exceeded: size {} over {}", size, max)
+            }
+            LimitError::Timeout(timeout) => write!(f, "timeout exceeded: {:?}", timeout),
+        }
+    }
+}
+
+impl std::error::Error for LimitError {}
+
+//Usage of a single evaluation, checked against its limits. Exceeding one is
+//returned as an Object::Error for the evaluator to pass up like any other
+//runtime error.
+#[derive(Debug)]
+pub struct Budget {
+    limits: Limits,
+    deadline: Option<Instant>,
+    steps: Cell<u64>,
+    call_depth: Cell<usize>,
+    expression_depth: Cell<usize>,
+    exceeded: Cell<Option<LimitError>>,
+}
+
+impl Budget {
+    pub fn new(limits: Limits) -> Self {
+        Budget {
+            limits,
+            deadline: limits.timeout.map(|timeout| Instant::now() + timeout),
+            steps: Cell::new(0),
+            call_depth: Cell::new(0),
+            expression_depth: Cell::new(0),
+            exceeded: Cell::new(None),
+        }
+    }
+
+    pub fn exceeded(&self) -> Option<LimitError> {
+        self.exceeded.get()
+    }
+
+    pub fn step(&self) -> Result<(), Object> {
+        let steps = self.steps.get() + 1;
+        self.steps.set(steps);
+
+        if let Some(max) = self.limits.max_steps {
+            if steps > max {
+                return Err(self.exceed(LimitError::Steps(max)));
+            }
+        }
+
+        match (self.deadline, self.limits.timeout) {
+            (Some(deadline), Some(timeout))
+                if steps.is_multiple_of(DEADLINE_INTERVAL) && Instant::now() >= deadline =>
+            {
+                Err(self.exceed(LimitError::Timeout(timeout)))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    //Every successful enter_call has to be paired with an exit_call
+    pub fn enter_call(&self) -> Result<(), Object> {
+        let depth = self.call_depth.get() + 1;
+        if depth > self.limits.max_call_depth {
+            return Err(self.exceed(LimitError::CallDepth(self.limits.max_call_depth)));
+        }
+
+        self.call_depth.set(depth);
+        Ok(())
+    }
+
+    pub fn exit_call(&self) {
+        self.call_depth.set(self.call_depth.get() - 1);
+    }
+
+    //Same pairing as enter_call, with exit_expression
+    pub fn enter_expression(&self) -> Result<(), Object> {
+        let depth = self.expression_depth.get() + 1;
+        if depth > self.limits.max_expression_depth {
+            return Err(self.exceed(LimitError::ExpressionDepth(
+                self.limits.max_expression_depth,
+            )));
+        }
+
+        self.expression_depth.set(depth);
+        Ok(())
+    }
+
+    pub fn exit_expression(&self) {
+        self.expression_depth.set(self.expression_depth.get() - 1);
+    }
+
+    //Values are checked once they exist, so a single operation can hold up
+    //to about twice the limit for a moment, but never keep it. Only one level
+    //is added at a time, so a value never gets deeper than one past its limit.
+    pub fn check_allocation(&self, value: Object) -> Object {
+        #[cfg(feature = "bignum")]
+        if let Object::BigInteger(integer) = &value {
+            return match self.reserve_integer(integer.bits().div_ceil(8) as usize) {
+                Ok(()) => value,
+                Err(error) => error,
+            };
+        }
+
+        let nested = matches!(value, Object::Array(_) | Object::Hash(_));
+        if self.limits.max_allocation.is_none() && !nested {
+            return value;
+        }
+
+        let (size, depth) = allocation_size(&value);
+        if depth > self.limits.max_value_depth {
+            return self.exceed(LimitError::ValueDepth(self.limits.max_value_depth));
+        }
+
+        match self.reserve(size) {
+            Ok(()) => value,
+            Err(error) => error,
+        }
+    }
+
+    //For results that are too large to compute before they are checked
+    pub fn reserve(&self, size: usize) -> Result<(), Object> {
+        match self.limits.max_allocation {
+            Some(max) if size > max => Err(self.exceed(LimitError::Allocation { size, max })),
+            _ => Ok(()),
+        }
+    }
+
+    //Same as reserve, for a big integer that also has to stay within the
+    //integer size
+    #[cfg(feature = "bignum")]
+    pub fn reserve_integer(&self, size: usize) -> Result<(), Object> {
+        let max = self.limits.max_integer_size;
+        if size > max {
+            return Err(self.exceed(LimitError::IntegerSize { size, max }));
+        }
+
+        self.reserve(size)
+    }
+
+    fn exceed(&self, error: LimitError) -> Object {
+        self.exceeded.set(Some(error));
+        Object::Error(error.to_string())
+    }
+}
+
+//Size and nesting depth of a value. Nested values are counted in full,
+//`[a, a]` holds two copies of `a`. Walks with its own stack, as values can be
+//nested deeper than the call stack allows.
+fn allocation_size(value: &Object) -> (usize, usize) {
+    let mut size = 0;
+    let mut max_depth = 0;
+    let mut pending = vec![(value, 0)];
+
+    while let Some((value, depth)) = pending.pop() {
+        match value {
+            Object::String(value) => size += value.len(),
+            #[cfg(feature = "bignum")]
+            Object::BigInteger(value) => size += value.bits().div_ceil(8) as usize,
+            Object::Array(elements) => {
+                size += elements.len();
+                max_depth = max_depth.max(depth + 1);
+                pending.extend(elements.iter().map(|element| (element, depth + 1)));
+            }
+            Object::Hash(hash) => {
+                size += hash.len();
+                max_depth = max_depth.max(depth + 1);
+                for (key, value) in hash.iter() {
+                    pending.push((key, depth + 1));
+                    pending.push((value, depth + 1));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    (size, max_depth)
+}
@@ -0,0 +1,201 @@
+use std::{collections::HashMap, fmt::Display, rc::Rc};
+
+#[cfg(feature = "bignum")]
+use crate::bignum::BigInt;
+use crate::{
+    builtins::Builtin,
+    environment::Env,
+    statements::{BlockStatement, Identifier},
+};
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Object {
+    Integer(i64),
+    //Only holds values outside of the i64 range, smaller ones are always an
+    //Integer so every number has a single representation
+    #[cfg(feature = "bignum")]
+    BigInteger(BigInt),
+    Float(f64),
+    Boolean(bool),
+    String(String),
+    Array(Vec<Object>),
+    Hash(HashObject),
+    Null,
+    ReturnValue(Box<Object>),
+    Error(String),
+    Function(Function),
+    Builtin(Builtin),
+}
+
+impl Object {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Object::Integer(_) => "INTEGER",
+            #[cfg(feature = "bignum")]
+            Object::BigInteger(_) => "INTEGER",
+            Object::Float(_) => "FLOAT",
+            Object::Boolean(_) => "BOOLEAN",
+            Object::String(_) => "STRING",
+            Object::Array(_) => "ARRAY",
+            Object::Hash(_) => "HASH",
+            Object::Null => "NULL",
+            Object::ReturnValue(_) => "RETURN_VALUE",
+            Object::Error(_) => "ERROR",
+            Object::Function(_) => "FUNCTION",
+            Object::Builtin(_) => "BUILTIN",
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        matches!(self, Object::Error(_))
+    }
+
+    //Only values with a stable notion of equality can be used as hash keys,
+    //which rules out floats as NaN is not equal to itself
+    pub fn hash_key(&self) -> Option<HashKey> {
+        match self {
+            Object::Integer(value) => Some(HashKey::Integer(*value)),
+            #[cfg(feature = "bignum")]
+            Object::BigInteger(value) => Some(HashKey::BigInteger(value.clone())),
+            Object::Boolean(value) => Some(HashKey::Boolean(*value)),
+            Object::String(value) => Some(HashKey::String(value.to_owned())),
+            _ => None,
+        }
+    }
+
+    //Strings are quoted when shown inside a collection so `["a, b"]` and
+    //`["a", "b"]` can be told apart
+    fn inspect_nested(&self) -> String {
+        match self {
+            Object::String(value) => format!("{:?}", value),
+            _ => self.to_string(),
+        }
+    }
+}
+
+impl Display for Object {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Object::Integer(value) => write!(f, "{}", value),
+            #[cfg(feature = "bignum")]
+            Object::BigInteger(value) => write!(f, "{}", value),
+            //Debug keeps the `.0` of whole numbers so `1.0` and `1` look different
+            Object::Float(value) => write!(f, "{:?}", value),
+            Object::Boolean(value) => write!(f, "{}", value),
+            Object::String(value) => write!(f, "{}", value),
+            Object::Array(elements) => {
+                let elements: Vec<String> = elements.iter().map(|e| e.inspect_nested()).collect();
+                write!(f, "[{}]", elements.join(", "))
+            }
+            Object::Hash(hash) => {
+                let pairs: Vec<String> = hash
+                    .iter()
+                    .map(|(key, value)| {
+                        format!("{}: {}", key.inspect_nested(), value.inspect_nested())
+                    })
+                    .collect();
+                write!(f, "{{{}}}", pairs.join(", "))
+            }
+            Object::Null => write!(f, "null"),
+            Object::ReturnValue(value) => write!(f, "{}", value),
+            Object::Error(message) => write!(f, "ERROR: {}", message),
+            Object::Function(function) => {
+                let parameters: Vec<&str> = function
+                    .parameters
+                    .iter()
+                    .map(|p| p.value.as_ref())
+                    .collect();
+                write!(f, "fn({}) {{ ... }}", parameters.join(", "))
+            }
+            Object::Builtin(builtin) => write!(f, "builtin {}", builtin.name),
+        }
+    }
+}
+
+//Narrows the result of big integer arithmetic back to an Integer if it fits
+#[cfg(feature = "bignum")]
+impl From<BigInt> for Object {
+    fn from(value: BigInt) -> Self {
+        match value.to_i64() {
+            Some(value) => Object::Integer(value),
+            None => Object::BigInteger(value),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Function {
+    pub parameters: Rc<[Identifier<'static>]>,
+    pub body: Rc<BlockStatement<'static>>,
+    pub env: Env,
+}
+
+//Two function values are the same if they were created from the same literal
+//in the same scope.
+impl PartialEq for Function {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.env, &other.env)
+            && self.parameters == other.parameters
+            && self.body == other.body
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub enum HashKey {
+    Integer(i64),
+    #[cfg(feature = "bignum")]
+    BigInteger(BigInt),
+    Boolean(bool),
+    String(String),
+}
+
+//Hash map that iterates in insertion order. Overwriting a key keeps its
+//original position.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct HashObject {
+    pairs: Vec<(Object, Object)>,
+    index: HashMap<HashKey, usize>,
+}
+
+impl HashObject {
+    pub fn new() -> Self {
+        HashObject {
+            pairs: vec![],
+            index: HashMap::new(),
+        }
+    }
+
+    //Returns the key back as an error if it cannot be hashed
+    pub fn insert(&mut self, key: Object, value: Object) -> Result<(), Object> {
+        let hash_key = match key.hash_key() {
+            Some(hash_key) => hash_key,
+            None => return Err(key),
+        };
+
+        match self.index.get(&hash_key) {
+            Some(position) => self.pairs[*position].1 = value,
+            None => {
+                self.index.insert(hash_key, self.pairs.len());
+                self.pairs.push((key, value));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn get(&self, key: &HashKey) -> Option<&Object> {
+        self.index.get(key).map(|position| &self.pairs[*position].1)
+    }
+
+    pub fn len(&self) -> usize {
+        self.pairs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pairs.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Object, &Object)> {
+        self.pairs.iter().map(|(key, value)| (key, value))
+    }
+}
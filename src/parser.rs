@@ -1,68 +1,122 @@
+use std::{io::IsTerminal, num::IntErrorKind};
+
+#[cfg(feature = "bignum")]
+use crate::bignum::BigInt;
 use crate::{
     ast::Programm,
+    diagnostic::Diagnostic,
     error::ParsingError,
     lexer::Lexer,
-    statements::{Expressions, Identifier, Statements},
+    span::Span,
+    statements::{BlockStatement, Expressions, FunctionCache, Identifier, Node, Statements},
     token::{Token, TokenType},
 };
 
-const BLANK: u8 = 0;
 const LOWEST: u8 = 1;
-const EQUALS: u8 = 2;
-const LESSGREATER: u8 = 3;
-const SUM: u8 = 4;
-const PRODUCT: u8 = 5;
-const PREFIX: u8 = 6;
-const CALL: u8 = 7;
+const ASSIGN: u8 = 2;
+const LOGICAL_OR: u8 = 3;
+const LOGICAL_AND: u8 = 4;
+//Bitwise operators bind like in C, so `a & mask == 0` is `a & (mask == 0)`
+const BIT_OR: u8 = 5;
+const BIT_XOR: u8 = 6;
+const BIT_AND: u8 = 7;
+const EQUALS: u8 = 8;
+const LESSGREATER: u8 = 9;
+const SHIFT: u8 = 10;
+const SUM: u8 = 11;
+const PRODUCT: u8 = 12;
+const PREFIX: u8 = 13;
+//Above PREFIX so `-2 ** 2` is `-(2 ** 2)`
+const POWER: u8 = 14;
+const CALL: u8 = 15;
+const INDEX: u8 = 16;
+
+//Each level of nesting is a few recursive calls, the limit keeps input like
+//`((((...` from overflowing the stack. Blocks count as a level of their own
+//as they add the most calls.
+const MAX_NESTING_DEPTH: usize = 128;
+
+//Chains like `1 + 2 + 3` or `f()()` are built in a loop and are not nesting,
+//but their tree is as deep as they are long and is cloned, compared and
+//dropped by recursion. The depth of every expression tree is bounded by this,
+//which is far above the nesting limit so long flat chains still parse.
+const MAX_TREE_DEPTH: usize = 1024;
 
 //helper function
+//Every token with a precedence above LOWEST needs an arm in Parser::infix_parse
 fn get_precedences(token_type: TokenType) -> u8 {
     match token_type {
+        TokenType::Assign => ASSIGN,
+        TokenType::PlusAssign => ASSIGN,
+        TokenType::MinusAssign => ASSIGN,
+        TokenType::AsteriskAssign => ASSIGN,
+        TokenType::SlashAssign => ASSIGN,
+        TokenType::Or => LOGICAL_OR,
+        TokenType::And => LOGICAL_AND,
+        TokenType::Pipe => BIT_OR,
+        TokenType::Caret => BIT_XOR,
+        TokenType::Ampersand => BIT_AND,
         TokenType::Equals => EQUALS,
         TokenType::NotEquals => EQUALS,
         TokenType::Lt => LESSGREATER,
         TokenType::Gt => LESSGREATER,
+        TokenType::LtEquals => LESSGREATER,
+        TokenType::GtEquals => LESSGREATER,
+        TokenType::ShiftLeft => SHIFT,
+        TokenType::ShiftRight => SHIFT,
         TokenType::Plus => SUM,
         TokenType::Minus => SUM,
         TokenType::Slash => PRODUCT,
         TokenType::Asterisk => PRODUCT,
+        TokenType::Percent => PRODUCT,
+        TokenType::Power => POWER,
+        TokenType::LParen => CALL,
+        TokenType::LBracket => INDEX,
         _ => LOWEST,
     }
 }
 
 #[derive(Debug)]
-pub struct Parser {
-    lexer: Lexer,
-    cur_token: Token,
-    peek_token: Token,
+pub struct Parser<'src> {
+    lexer: Lexer<'src>,
+    cur_token: Token<'src>,
+    peek_token: Token<'src>,
     errors: Vec<ParsingError>,
+    //Expressions currently being parsed, see parse_expression
+    depth: usize,
+    //Depth of the deepest tree among the expressions parsed since the node
+    //currently being built was started, see parse_nested_expression
+    tree_depth: usize,
 }
 
 //Main impl
-impl Parser {
-    pub fn new(mut lexer: Lexer) -> Self {
+impl<'src> Parser<'src> {
+    pub fn new(mut lexer: Lexer<'src>) -> Self {
         let cur_token = lexer.next_token();
         let peek_token = lexer.next_token();
-        let errors = vec![];
+        let errors = lexer
+            .take_errors()
+            .into_iter()
+            .map(|e| ParsingError(Box::new(e)))
+            .collect();
 
         Parser {
             lexer,
             cur_token,
             peek_token,
             errors,
+            depth: 0,
+            tree_depth: 0,
         }
     }
 
-    pub fn parse_programm(&mut self) -> Option<Programm> {
+    //Always returns a programm, statements that failed to parse are kept as
+    //Statements::Error and the reasons are available through errors()
+    pub fn parse_programm(&mut self) -> Option<Programm<'src>> {
         let mut statements = vec![];
         while self.cur_token.token_type != TokenType::Eof {
-            let statement = self.parse_statement();
-
-            if let Err(e) = statement {
-                self.errors.push(e);
-            } else {
-                statements.push(statement.expect("the world is ending"));
-            }
+            let (statement, _) = self.parse_statement_or_recover();
+            statements.push(statement);
 
             self.next_token();
         }
@@ -72,165 +126,491 @@ impl Parser {
 }
 
 //Statements
-impl Parser {
-    fn parse_statement(&mut self) -> Result<Statements, ParsingError> {
-        let token = &self.cur_token;
-        println!("Token to parse : {:?}", token);
-        match token.token_type {
+impl<'src> Parser<'src> {
+    fn parse_statement(&mut self) -> Result<Statements<'src>, ParsingError> {
+        match self.cur_token.token_type {
             TokenType::Let => self.parse_let_statement(),
             TokenType::Return => self.parse_return_statement(),
             _ => self.parse_expression_statement(),
         }
     }
 
-    fn parse_let_statement(&mut self) -> Result<Statements, ParsingError> {
+    //On failure the error is recorded and the parser skips to the end of the
+    //broken statement. The returned flag is set if it stopped on a `}` that
+    //closes the enclosing block rather than belonging to the statement.
+    fn parse_statement_or_recover(&mut self) -> (Statements<'src>, bool) {
+        let start = self.cur_token.span;
+
+        match self.parse_statement() {
+            Ok(statement) => (statement, false),
+            Err(e) => {
+                self.errors.push(e);
+                let at_block_end = self.synchronize();
+                let span = if at_block_end {
+                    start
+                } else {
+                    start.to(self.cur_token.span)
+                };
+
+                (Statements::Error(span), at_block_end)
+            }
+        }
+    }
+
+    fn parse_let_statement(&mut self) -> Result<Statements<'src>, ParsingError> {
         let let_token = self.cur_token.clone();
         self.expect_peek(TokenType::Ident)?;
 
         let identifier = Identifier {
             token: self.cur_token.clone(),
-            value: self.cur_token.literal.to_owned(),
+            value: self.cur_token.literal.clone(),
         };
 
         self.expect_peek(TokenType::Assign)?;
+        self.next_token();
+
+        let value = self.parse_expression(LOWEST)?;
 
-        //TODO: expressions
-        while !self.cur_token_is(TokenType::Semicolon) {
+        if self.peek_token_is(TokenType::Semicolon) {
             self.next_token();
         }
 
-        Ok(Statements::Let(
-            let_token,
-            identifier,
-            Expressions::Variant1,
-        ))
+        Ok(Statements::Let(let_token, identifier, value))
     }
 
-    fn parse_return_statement(&mut self) -> Result<Statements, ParsingError> {
+    fn parse_return_statement(&mut self) -> Result<Statements<'src>, ParsingError> {
         let cur_token = self.cur_token.clone();
-
         self.next_token();
 
-        //TODO: expressions
-        while !self.cur_token_is(TokenType::Semicolon) {
+        let value = self.parse_expression(LOWEST)?;
+
+        if self.peek_token_is(TokenType::Semicolon) {
             self.next_token();
         }
 
-        Ok(Statements::Return(cur_token, Expressions::Variant1))
+        Ok(Statements::Return(cur_token, value))
     }
 
-    fn parse_expression_statement(&mut self) -> Result<Statements, ParsingError> {
+    fn parse_expression_statement(&mut self) -> Result<Statements<'src>, ParsingError> {
         let cur_token = self.cur_token.clone();
-        let expression = self.parse_expression(LOWEST);
+        let expression = self.parse_expression(LOWEST)?;
 
         if self.peek_token_is(TokenType::Semicolon) {
             self.next_token();
         }
 
-        //TODO: no unwrap
-        Ok(Statements::Expression(
-            cur_token,
-            expression.unwrap_or(Expressions::Variant1),
-        ))
+        Ok(Statements::Expression(cur_token, expression))
+    }
+
+    fn parse_block_statement(&mut self) -> Result<BlockStatement<'src>, ParsingError> {
+        self.enter_nesting()?;
+        let result = self.parse_block_statements();
+        self.depth -= 1;
+
+        result
+    }
+
+    fn parse_block_statements(&mut self) -> Result<BlockStatement<'src>, ParsingError> {
+        let token = self.cur_token.clone();
+        let mut statements = vec![];
+        self.next_token();
+
+        while !self.cur_token_is(TokenType::RBrace) && !self.cur_token_is(TokenType::Eof) {
+            let (statement, at_block_end) = self.parse_statement_or_recover();
+            statements.push(statement);
+
+            if at_block_end {
+                break;
+            }
+
+            self.next_token();
+        }
+
+        if self.cur_token_is(TokenType::Eof) {
+            return Err(ParsingError(Box::new(
+                Diagnostic::error("Expected RBrace, found end of input", self.cur_token.span)
+                    .with_code("E0001")
+                    .with_label("expected RBrace here")
+                    .with_secondary(token.span, "block opened here"),
+            )));
+        }
+
+        let span = token.span.to(self.cur_token.span);
+        Ok(BlockStatement {
+            token,
+            statements,
+            span,
+        })
+    }
+
+    //Skips tokens until the end of the current statement: a `;` or the token
+    //before a `}` or a keyword starting the next statement. Braces opened while
+    //skipping are skipped as a whole.
+    fn synchronize(&mut self) -> bool {
+        let mut depth = 0;
+
+        loop {
+            match self.cur_token.token_type {
+                TokenType::Eof => return false,
+                TokenType::LBrace => depth += 1,
+                TokenType::RBrace if depth == 0 => return true,
+                TokenType::RBrace => depth -= 1,
+                TokenType::Semicolon if depth == 0 => return false,
+                _ => {}
+            }
+
+            if depth == 0
+                && matches!(
+                    self.peek_token.token_type,
+                    TokenType::RBrace | TokenType::Let | TokenType::Return | TokenType::Eof
+                )
+            {
+                return false;
+            }
+
+            self.next_token();
+        }
     }
 }
 
 //Expressions
-impl Parser {
-    fn parse_expression(&mut self, precedence: u8) -> Option<Expressions> {
-        let prefix = self.prefix_parse();
-        if prefix.is_none() {
-            return None;
-        }
-        let mut left_exp = prefix;
+impl<'src> Parser<'src> {
+    //Every nested expression and argument list passes through here, so this
+    //is where nesting is limited
+    fn parse_expression(&mut self, precedence: u8) -> Result<Expressions<'src>, ParsingError> {
+        let depth = self.depth;
+        let tree_depth = self.tree_depth;
+        self.enter_nesting()?;
+        let result = self.parse_nested_expression(precedence);
+        self.depth = depth;
+        //The caller builds its node on top of this expression
+        self.tree_depth = tree_depth.max(self.tree_depth);
+
+        result
+    }
+
+    fn parse_nested_expression(
+        &mut self,
+        precedence: u8,
+    ) -> Result<Expressions<'src>, ParsingError> {
+        //Every expression parsed for a node raises tree_depth to its own
+        //depth, the node is one level above the deepest of them
+        self.tree_depth = 0;
+        let mut left_exp = self.prefix_parse()?;
+        let mut tree_depth = self.tree_depth + 1;
 
         while !self.peek_token_is(TokenType::Semicolon) && precedence < self.peek_precedence() {
-            println!("left exp : {:?}", left_exp);
-            let infix = self.infix_parse(left_exp.clone().unwrap());
-            println!("parsed infix : {:?}", infix);
+            if tree_depth >= MAX_TREE_DEPTH {
+                return Err(self.tree_too_deep());
+            }
+
+            self.tree_depth = 0;
+            left_exp = self.infix_parse(left_exp)?;
+            tree_depth = tree_depth.max(self.tree_depth) + 1;
+        }
+
+        self.tree_depth = tree_depth;
+        Ok(left_exp)
+    }
+
+    fn parse_integer_literal(&self) -> Result<Expressions<'src>, ParsingError> {
+        let cur_token = self.cur_token.clone();
+        let (radix, digits) = self.integer_digits()?;
+
+        match i64::from_str_radix(&digits, radix) {
+            Ok(value) => Ok(Expressions::IntegerLiteral(cur_token, value)),
+            #[cfg(feature = "bignum")]
+            Err(e) if *e.kind() == IntErrorKind::PosOverflow => {
+                match BigInt::parse_radix(&digits, radix) {
+                    Some(value) => Ok(Expressions::BigIntegerLiteral(cur_token, value)),
+                    None => Err(self.invalid_integer_error()),
+                }
+            }
+            #[cfg(not(feature = "bignum"))]
+            Err(e) if *e.kind() == IntErrorKind::PosOverflow => Err(ParsingError(Box::new(
+                Diagnostic::error(
+                    &format!("Integer literal {} is too large", cur_token.literal),
+                    cur_token.span,
+                )
+                .with_code("E0004")
+                .with_label("does not fit in a 64-bit signed integer")
+                .with_note(&format!("the largest integer is {}", i64::MAX)),
+            ))),
+            Err(_) => Err(self.invalid_integer_error()),
+        }
+    }
 
-            if infix.is_none() {
-                return left_exp;
+    //Splits off the radix prefix and returns the digits without separators.
+    //Errors point at the offending part of the literal.
+    fn integer_digits(&self) -> Result<(u32, String), ParsingError> {
+        let literal = &self.cur_token.literal;
+        let (radix, name) = match literal.get(..2) {
+            Some("0x" | "0X") => (16, "hexadecimal"),
+            Some("0o" | "0O") => (8, "octal"),
+            Some("0b" | "0B") => (2, "binary"),
+            _ => (10, "decimal"),
+        };
+        let prefix = if radix == 10 { 0 } else { 2 };
+
+        if literal.len() == prefix {
+            return Err(ParsingError(Box::new(
+                Diagnostic::error(
+                    &format!("Missing digits after {}", literal),
+                    self.cur_token.span,
+                )
+                .with_code("E0004")
+                .with_label(&format!("expected {} digits", name))
+                .with_help("write prefixed numbers like 0x1F, 0o17 or 0b1010"),
+            )));
+        }
+
+        for (offset, ch) in literal.char_indices().skip(prefix) {
+            if ch != '_' && !ch.is_digit(radix) {
+                return Err(ParsingError(Box::new(
+                    Diagnostic::error(
+                        &format!("Invalid digit {} in {} literal", ch, name),
+                        self.literal_span(offset, ch.len_utf8()),
+                    )
+                    .with_code("E0004")
+                    .with_label(&format!("not a {} digit", name)),
+                )));
             }
+        }
+        self.check_separators(prefix, radix)?;
 
-            // Maybe necessary later on
-            // self.next_token();
+        Ok((radix, literal[prefix..].replace('_', "")))
+    }
+
+    //A `_` has to sit between two digits, so `1_000` is fine but `1__0`,
+    //`0x_1` and `7_` are not
+    fn check_separators(&self, start: usize, radix: u32) -> Result<(), ParsingError> {
+        let literal = self.cur_token.literal.as_bytes();
+        let is_digit = |offset: Option<usize>| {
+            offset
+                .filter(|offset| *offset >= start)
+                .and_then(|offset| literal.get(offset))
+                .is_some_and(|byte| (*byte as char).is_digit(radix))
+        };
 
-            left_exp = infix;
+        for (offset, byte) in literal.iter().enumerate().skip(start) {
+            if *byte == b'_' && !(is_digit(offset.checked_sub(1)) && is_digit(Some(offset + 1))) {
+                return Err(ParsingError(Box::new(
+                    Diagnostic::error("Misplaced digit separator", self.literal_span(offset, 1))
+                        .with_code("E0004")
+                        .with_label("`_` must be between two digits")
+                        .with_help("separate digits like 1_000_000"),
+                )));
+            }
         }
 
-        left_exp
+        Ok(())
     }
 
-    fn parse_integer_literal(&self) -> Option<Expressions> {
+    fn invalid_integer_error(&self) -> ParsingError {
+        ParsingError(Box::new(
+            Diagnostic::error(
+                &format!("Invalid integer literal {}", self.cur_token.literal),
+                self.cur_token.span,
+            )
+            .with_code("E0004"),
+        ))
+    }
+
+    fn parse_float_literal(&self) -> Result<Expressions<'src>, ParsingError> {
         let cur_token = self.cur_token.clone();
-        let value = cur_token.literal.parse();
-        if value.is_err() {
-            return None;
+        self.check_separators(0, 10)?;
+
+        match cur_token.literal.replace('_', "").parse() {
+            Ok(value) => Ok(Expressions::FloatLiteral(cur_token, value)),
+            Err(_) => Err(ParsingError(Box::new(
+                Diagnostic::error(
+                    &format!("Invalid float literal {}", cur_token.literal),
+                    cur_token.span,
+                )
+                .with_code("E0006")
+                .with_help("write exponents with digits, like 1e9 or 2.5e-3"),
+            ))),
         }
-
-        Some(Expressions::IntegerLiteral(cur_token, value.unwrap()))
     }
 
-    fn parse_prefix_expression(&mut self) -> Option<Expressions> {
+    fn parse_prefix_expression(&mut self) -> Result<Expressions<'src>, ParsingError> {
         let cur_token = self.cur_token.clone();
-        let operator = cur_token.literal.to_owned();
+        let operator = cur_token.literal.clone();
         self.next_token();
 
-        //TODO: Safe unwrap
-        let exp = self.parse_expression(PREFIX).expect("Should not fail");
+        let exp = self.parse_expression(PREFIX)?;
 
-        Some(Expressions::PrefixExpression(
+        Ok(Expressions::PrefixExpression(
             cur_token,
             operator,
             Box::new(exp),
         ))
     }
 
-    fn parse_boolean(&self) -> Option<Expressions> {
-        Some(Expressions::BooleanExpression(
+    fn parse_string_literal(&self) -> Result<Expressions<'src>, ParsingError> {
+        Ok(Expressions::StringLiteral(
+            self.cur_token.clone(),
+            self.cur_token.literal.clone(),
+        ))
+    }
+
+    fn parse_identifier(&self) -> Result<Expressions<'src>, ParsingError> {
+        Ok(Expressions::Identifier(Identifier {
+            token: self.cur_token.clone(),
+            value: self.cur_token.literal.clone(),
+        }))
+    }
+
+    fn parse_boolean(&self) -> Result<Expressions<'src>, ParsingError> {
+        Ok(Expressions::BooleanExpression(
             self.cur_token.clone(),
             self.cur_token_is(TokenType::True),
         ))
     }
 
-    fn parse_grouped_expression(&mut self) -> Option<Expressions> {
+    fn parse_grouped_expression(&mut self) -> Result<Expressions<'src>, ParsingError> {
         self.next_token();
 
-        let exp = self.parse_expression(LOWEST);
+        let exp = self.parse_expression(LOWEST)?;
+        self.expect_peek(TokenType::RParen)?;
 
-        if self.expect_peek(TokenType::RParen).is_err() {
-            None
-        } else {
-            exp
+        Ok(exp)
+    }
+
+    fn parse_function_literal(&mut self) -> Result<Expressions<'src>, ParsingError> {
+        let cur_token = self.cur_token.clone();
+
+        self.expect_peek(TokenType::LParen)?;
+        let parameters = self.parse_function_parameters()?;
+
+        self.expect_peek(TokenType::LBrace)?;
+        let body = self.parse_block_statement()?;
+
+        Ok(Expressions::FunctionLiteral(
+            cur_token,
+            parameters,
+            body,
+            FunctionCache::default(),
+        ))
+    }
+
+    fn parse_function_parameters(&mut self) -> Result<Vec<Identifier<'src>>, ParsingError> {
+        let mut identifiers = vec![];
+
+        if self.peek_token_is(TokenType::RParen) {
+            self.next_token();
+            return Ok(identifiers);
         }
+
+        loop {
+            self.expect_peek(TokenType::Ident)?;
+            identifiers.push(Identifier {
+                token: self.cur_token.clone(),
+                value: self.cur_token.literal.clone(),
+            });
+
+            if !self.peek_token_is(TokenType::Comma) {
+                break;
+            }
+            self.next_token();
+        }
+
+        self.expect_peek(TokenType::RParen)?;
+
+        Ok(identifiers)
+    }
+
+    fn parse_if_expression(&mut self) -> Result<Expressions<'src>, ParsingError> {
+        let cur_token = self.cur_token.clone();
+
+        self.expect_peek(TokenType::LParen)?;
+        self.next_token();
+        let condition = self.parse_expression(LOWEST)?;
+        self.expect_peek(TokenType::RParen)?;
+
+        self.expect_peek(TokenType::LBrace)?;
+        let consequence = self.parse_block_statement()?;
+
+        let mut alternative = None;
+        if self.peek_token_is(TokenType::Else) {
+            self.next_token();
+
+            if self.peek_token_is(TokenType::If) {
+                self.next_token();
+                let token = self.cur_token.clone();
+                //Each `else if` nests the rest of the chain one level deeper
+                self.enter_nesting()?;
+                let nested = self.parse_if_expression();
+                self.depth -= 1;
+                let nested = nested?;
+                let span = nested.span();
+
+                alternative = Some(BlockStatement {
+                    token: token.clone(),
+                    statements: vec![Statements::Expression(token, nested)],
+                    span,
+                });
+            } else {
+                self.expect_peek(TokenType::LBrace)?;
+                alternative = Some(self.parse_block_statement()?);
+            }
+        }
+
+        Ok(Expressions::IfExpression(
+            cur_token,
+            Box::new(condition),
+            consequence,
+            alternative,
+        ))
     }
 
-    fn prefix_parse(&mut self) -> Option<Expressions> {
+    fn prefix_parse(&mut self) -> Result<Expressions<'src>, ParsingError> {
         match self.cur_token.token_type {
-            TokenType::Illegal => Some(Expressions::Variant1),
+            TokenType::Illegal => Err(ParsingError(Box::new(
+                Diagnostic::error(
+                    &format!("Illegal character {}", self.cur_token.literal),
+                    self.cur_token.span,
+                )
+                .with_code("E0003"),
+            ))),
+            TokenType::Ident => self.parse_identifier(),
             TokenType::Int => self.parse_integer_literal(),
+            TokenType::Float => self.parse_float_literal(),
+            TokenType::String => self.parse_string_literal(),
             TokenType::Bang => self.parse_prefix_expression(),
             TokenType::Minus => self.parse_prefix_expression(),
+            TokenType::Tilde => self.parse_prefix_expression(),
             TokenType::True => self.parse_boolean(),
             TokenType::False => self.parse_boolean(),
             TokenType::LParen => self.parse_grouped_expression(),
-            _ => None,
+            TokenType::Function => self.parse_function_literal(),
+            TokenType::If => self.parse_if_expression(),
+            TokenType::LBracket => self.parse_array_literal(),
+            //Blocks only follow `fn(...)`, `if (...)` and `else`, which parse them
+            //directly, so a `{` in expression position always starts a hash
+            TokenType::LBrace => self.parse_hash_literal(),
+            _ => Err(self.expected_expression_error()),
         }
     }
 
-    fn parse_infix_expression(&mut self, left: Expressions) -> Option<Expressions> {
+    fn parse_infix_expression(
+        &mut self,
+        left: Expressions<'src>,
+    ) -> Result<Expressions<'src>, ParsingError> {
         //Remember this is not like in the book
         self.next_token();
         let cur_token = self.cur_token.clone();
-        let operator = cur_token.literal.to_owned();
-        let cur_precedence = self.cur_precedence();
+        let operator = cur_token.literal.clone();
+        let mut cur_precedence = self.cur_precedence();
+        //Right associative, `2 ** 3 ** 2` is `2 ** (3 ** 2)`
+        if self.cur_token_is(TokenType::Power) {
+            cur_precedence -= 1;
+        }
         self.next_token();
 
-        //TODO: safe unwrap
-        let right = self.parse_expression(cur_precedence).unwrap();
+        let right = self.parse_expression(cur_precedence)?;
 
-        Some(Expressions::InfixExpression(
+        Ok(Expressions::InfixExpression(
             cur_token,
             Box::new(left),
             operator,
@@ -238,26 +618,201 @@ impl Parser {
         ))
     }
 
-    fn infix_parse(&mut self, left: Expressions) -> Option<Expressions> {
+    fn infix_parse(&mut self, left: Expressions<'src>) -> Result<Expressions<'src>, ParsingError> {
         match self.peek_token.token_type {
             TokenType::Plus
             | TokenType::Minus
             | TokenType::Slash
             | TokenType::Asterisk
+            | TokenType::Percent
+            | TokenType::Power
             | TokenType::Equals
             | TokenType::NotEquals
             | TokenType::Lt
-            | TokenType::Gt => self.parse_infix_expression(left),
-            _ => None,
+            | TokenType::Gt
+            | TokenType::LtEquals
+            | TokenType::GtEquals
+            | TokenType::And
+            | TokenType::Or
+            | TokenType::Ampersand
+            | TokenType::Pipe
+            | TokenType::Caret
+            | TokenType::ShiftLeft
+            | TokenType::ShiftRight => self.parse_infix_expression(left),
+            TokenType::LParen => self.parse_call_expression(left),
+            TokenType::LBracket => self.parse_index_expression(left),
+            TokenType::Assign
+            | TokenType::PlusAssign
+            | TokenType::MinusAssign
+            | TokenType::AsteriskAssign
+            | TokenType::SlashAssign => self.parse_assign_expression(left),
+            _ => Ok(left),
+        }
+    }
+
+    //Assignments are right associative, so the value is parsed with a lower
+    //precedence than ASSIGN itself to let `a = b = 1` group as `a = (b = 1)`
+    fn parse_assign_expression(
+        &mut self,
+        target: Expressions<'src>,
+    ) -> Result<Expressions<'src>, ParsingError> {
+        self.next_token();
+        let cur_token = self.cur_token.clone();
+        let operator = cur_token.literal.clone();
+
+        let name = match target {
+            Expressions::Identifier(identifier) => identifier,
+            _ => {
+                return Err(ParsingError(Box::new(
+                    Diagnostic::error("Invalid assignment target", target.span())
+                        .with_code("E0005")
+                        .with_label("cannot assign to this expression")
+                        .with_help("only variables can be assigned to"),
+                )))
+            }
+        };
+
+        self.next_token();
+        let value = self.parse_expression(ASSIGN - 1)?;
+
+        Ok(Expressions::AssignExpression(
+            cur_token,
+            name,
+            operator,
+            Box::new(value),
+        ))
+    }
+
+    fn parse_call_expression(
+        &mut self,
+        function: Expressions<'src>,
+    ) -> Result<Expressions<'src>, ParsingError> {
+        self.next_token();
+        let cur_token = self.cur_token.clone();
+        let arguments = self.parse_expression_list(TokenType::RParen)?;
+        let span = function.span().to(self.cur_token.span);
+
+        Ok(Expressions::CallExpression(
+            cur_token,
+            Box::new(function),
+            arguments,
+            span,
+        ))
+    }
+
+    //Comma separated expressions up to and including the end token
+    fn parse_expression_list(
+        &mut self,
+        end: TokenType,
+    ) -> Result<Vec<Expressions<'src>>, ParsingError> {
+        let mut arguments = vec![];
+
+        if self.peek_token_is(end) {
+            self.next_token();
+            return Ok(arguments);
+        }
+
+        self.next_token();
+        arguments.push(self.parse_expression(LOWEST)?);
+
+        while self.peek_token_is(TokenType::Comma) {
+            self.next_token();
+            self.next_token();
+            arguments.push(self.parse_expression(LOWEST)?);
+        }
+
+        self.expect_peek(end)?;
+
+        Ok(arguments)
+    }
+
+    fn parse_array_literal(&mut self) -> Result<Expressions<'src>, ParsingError> {
+        let cur_token = self.cur_token.clone();
+        let elements = self.parse_expression_list(TokenType::RBracket)?;
+        let span = cur_token.span.to(self.cur_token.span);
+
+        Ok(Expressions::ArrayLiteral(cur_token, elements, span))
+    }
+
+    fn parse_hash_literal(&mut self) -> Result<Expressions<'src>, ParsingError> {
+        let cur_token = self.cur_token.clone();
+        let mut pairs = vec![];
+
+        while !self.peek_token_is(TokenType::RBrace) {
+            self.next_token();
+            let key = self.parse_expression(LOWEST)?;
+
+            self.expect_peek(TokenType::Colon)?;
+            self.next_token();
+            let value = self.parse_expression(LOWEST)?;
+
+            pairs.push((key, value));
+
+            if !self.peek_token_is(TokenType::RBrace) {
+                self.expect_peek(TokenType::Comma)?;
+            }
+        }
+
+        self.expect_peek(TokenType::RBrace)?;
+        let span = cur_token.span.to(self.cur_token.span);
+
+        Ok(Expressions::HashLiteral(cur_token, pairs, span))
+    }
+
+    //Parses both `left[index]` and `left[start:end]`
+    fn parse_index_expression(
+        &mut self,
+        left: Expressions<'src>,
+    ) -> Result<Expressions<'src>, ParsingError> {
+        self.next_token();
+        let cur_token = self.cur_token.clone();
+
+        let mut start = None;
+        if !self.peek_token_is(TokenType::Colon) {
+            self.next_token();
+            start = Some(Box::new(self.parse_expression(LOWEST)?));
+        }
+
+        if !self.peek_token_is(TokenType::Colon) {
+            self.expect_peek(TokenType::RBracket)?;
+            let span = left.span().to(self.cur_token.span);
+            let index = start.ok_or_else(|| self.expected_expression_error())?;
+
+            return Ok(Expressions::IndexExpression(
+                cur_token,
+                Box::new(left),
+                index,
+                span,
+            ));
+        }
+
+        self.next_token();
+        let mut end = None;
+        if !self.peek_token_is(TokenType::RBracket) {
+            self.next_token();
+            end = Some(Box::new(self.parse_expression(LOWEST)?));
         }
+        self.expect_peek(TokenType::RBracket)?;
+        let span = left.span().to(self.cur_token.span);
+
+        Ok(Expressions::SliceExpression(
+            cur_token,
+            Box::new(left),
+            start,
+            end,
+            span,
+        ))
     }
 }
 
 //Helper
-impl Parser {
+impl<'src> Parser<'src> {
     fn next_token(&mut self) {
-        self.cur_token = self.peek_token.clone();
-        self.peek_token = self.lexer.next_token();
+        self.cur_token = std::mem::replace(&mut self.peek_token, self.lexer.next_token());
+
+        for error in self.lexer.take_errors() {
+            self.errors.push(ParsingError(Box::new(error)));
+        }
     }
 
     fn cur_token_is(&self, token_type: TokenType) -> bool {
@@ -274,13 +829,79 @@ impl Parser {
             return Ok(());
         }
 
-        Err(ParsingError(format!(
-            "Expected {:?}, found {}",
-            token_type,
-            self.peek_token.literal.to_owned()
+        Err(ParsingError(Box::new(
+            Diagnostic::error(
+                &format!(
+                    "Expected {:?}, found {}",
+                    token_type,
+                    describe_token(&self.peek_token)
+                ),
+                self.peek_token.span,
+            )
+            .with_code("E0001")
+            .with_label(&format!("expected {:?} here", token_type)),
         )))
     }
 
+    fn expected_expression_error(&self) -> ParsingError {
+        ParsingError(Box::new(
+            Diagnostic::error(
+                &format!(
+                    "Expected expression, found {}",
+                    describe_token(&self.cur_token)
+                ),
+                self.cur_token.span,
+            )
+            .with_code("E0002")
+            .with_label("expected an expression"),
+        ))
+    }
+
+    //Part of the current token, which has to be on a single line
+    fn literal_span(&self, offset: usize, len: usize) -> Span {
+        let span = self.cur_token.span;
+        Span::new(
+            span.start + offset,
+            span.start + offset + len,
+            span.line,
+            span.column + offset,
+        )
+    }
+
+    //Has to be paired with restoring depth once the nested part is parsed
+    fn enter_nesting(&mut self) -> Result<(), ParsingError> {
+        if self.depth >= MAX_NESTING_DEPTH {
+            return Err(ParsingError(Box::new(
+                Diagnostic::error("Expression is nested too deeply", self.cur_token.span)
+                    .with_code("E0007")
+                    .with_label(&format!(
+                        "more than {} levels of nesting",
+                        MAX_NESTING_DEPTH
+                    ))
+                    .with_help(&format!(
+                        "nesting is limited to {} levels, chains like `a + b + c` to {} links; move parts of the expression into variables with let",
+                        MAX_NESTING_DEPTH, MAX_TREE_DEPTH
+                    )),
+            )));
+        }
+
+        self.depth += 1;
+        Ok(())
+    }
+
+    //Reported on the operator that would make the tree too deep
+    fn tree_too_deep(&self) -> ParsingError {
+        ParsingError(Box::new(
+            Diagnostic::error("Expression is nested too deeply", self.peek_token.span)
+                .with_code("E0007")
+                .with_label(&format!("more than {} links in a chain", MAX_TREE_DEPTH))
+                .with_help(&format!(
+                    "chains are limited to {} links, apart from the {} levels of nesting; move parts of the chain into variables with let",
+                    MAX_TREE_DEPTH, MAX_NESTING_DEPTH
+                )),
+        ))
+    }
+
     fn peek_precedence(&self) -> u8 {
         get_precedences(self.peek_token.token_type)
     }
@@ -289,18 +910,35 @@ impl Parser {
         get_precedences(self.cur_token.token_type)
     }
 
+    pub fn errors(&self) -> &[ParsingError] {
+        &self.errors
+    }
+
+    pub fn take_errors(&mut self) -> Vec<ParsingError> {
+        std::mem::take(&mut self.errors)
+    }
+
     pub fn print_errors(&self) {
+        let color = std::io::stdout().is_terminal();
         for error in self.errors.iter() {
-            println!("{}", error.0);
+            println!("{}", error.0.render(self.lexer.input(), color));
         }
     }
 }
 
+fn describe_token(token: &Token) -> String {
+    match token.token_type {
+        TokenType::Eof => "end of input".to_string(),
+        _ => token.literal.to_string(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::{
         lexer::Lexer,
+        span::Span,
         statements::{Node, Statements},
     };
 
@@ -324,15 +962,16 @@ mod tests {
         let programm = programm.unwrap();
         assert_eq!(programm.statements.len(), 3);
 
-        let expected = vec!["x", "y", "foobar"];
+        let expected = [("x", 5), ("y", 10), ("foobar", 838383)];
 
         for (index, statement) in programm.statements.iter().enumerate() {
-            if let Statements::Let(_, identifier, _) = statement {
+            if let Statements::Let(_, identifier, value) = statement {
                 assert_eq!(statement.token_literal(), "let");
 
-                let name = expected[index].to_string();
+                let name = expected[index].0.to_string();
                 assert_eq!(identifier.value, name);
                 assert_eq!(identifier.token_literal(), name);
+                assert!(test_integer_literal(value, expected[index].1));
             } else {
                 panic!("statement is not a let statement");
             }
@@ -358,18 +997,157 @@ mod tests {
 
         assert_eq!(programm.statements.len(), 3);
 
-        for statement in programm.statements.iter() {
-            if let Statements::Return(token, _) = statement {
+        let expected = [5, 10, 993322];
+
+        for (index, statement) in programm.statements.iter().enumerate() {
+            if let Statements::Return(token, value) = statement {
                 assert_eq!(token.literal, "return");
+                assert!(test_integer_literal(value, expected[index]));
             } else {
                 panic!("expected return statement");
             }
         }
     }
 
+    #[test]
+    fn optional_semicolon_test() {
+        let test_inputs = [
+            ("let x = 5", "x"),
+            ("let y = fn(a) { a }", "y"),
+            ("return 5", "return"),
+            ("return add(1, 2)", "return"),
+        ];
+
+        for input in test_inputs.iter() {
+            let lexer = Lexer::new(input.0);
+            let mut parser = Parser::new(lexer);
+
+            let programm = parser.parse_programm().unwrap();
+            assert!(parser.errors().is_empty(), "{}", input.0);
+            assert_eq!(1, programm.statements.len(), "{}", input.0);
+
+            match &programm.statements[0] {
+                Statements::Let(_, identifier, _) => assert_eq!(input.1, identifier.value),
+                Statements::Return(token, _) => assert_eq!(input.1, token.literal),
+                _ => panic!("expected let or return statement"),
+            }
+        }
+    }
+
+    #[test]
+    fn missing_statement_value_test() {
+        let test_inputs = ["let x =", "let x = ;", "return", "return;"];
+
+        for input in test_inputs.iter() {
+            let lexer = Lexer::new(input);
+            let mut parser = Parser::new(lexer);
+
+            let programm = parser.parse_programm().unwrap();
+            assert_eq!(1, parser.errors().len(), "{}", input);
+            assert_eq!(1, programm.statements.len(), "{}", input);
+            assert!(matches!(programm.statements[0], Statements::Error(_)));
+        }
+    }
+
+    #[test]
+    fn malformed_input_does_not_panic_test() {
+        let test_inputs = [
+            "-;",
+            "5 +",
+            "!",
+            "(1 + 2",
+            "fn(x, ) {}",
+            "fn(1) {}",
+            "add(1, 2",
+            "let",
+            "let 5",
+            ")",
+            "}",
+            "fn() {",
+            "@",
+            "5 + * 3; 1",
+        ];
+
+        for input in test_inputs.iter() {
+            let lexer = Lexer::new(input);
+            let mut parser = Parser::new(lexer);
+
+            let programm = parser.parse_programm().unwrap();
+            assert!(!parser.errors().is_empty(), "{}", input);
+            assert!(
+                programm
+                    .statements
+                    .iter()
+                    .any(|s| matches!(s, Statements::Error(_))),
+                "{}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn error_recovery_test() {
+        let input = "let x 5 * 5; let y = 2;\n-; y + 1\nlet = fn(a) { a }\nreturn y";
+
+        let lexer = Lexer::new(input);
+        let mut parser = Parser::new(lexer);
+
+        let programm = parser.parse_programm().unwrap();
+
+        let lines: Vec<usize> = parser.errors().iter().map(|e| e.0.span().line).collect();
+        assert_eq!(vec![1, 2, 3], lines);
+
+        let kinds: Vec<&str> = programm
+            .statements
+            .iter()
+            .map(|s| match s {
+                Statements::Let(_, _, _) => "let",
+                Statements::Return(_, _) => "return",
+                Statements::Expression(_, _) => "expression",
+                Statements::Error(_) => "error",
+            })
+            .collect();
+        assert_eq!(
+            vec!["error", "let", "error", "expression", "error", "return"],
+            kinds
+        );
+
+        if let Statements::Error(span) = &programm.statements[0] {
+            assert_eq!("let x 5 * 5;", &input[span.start..span.end]);
+        }
+    }
+
+    #[test]
+    fn error_recovery_in_block_test() {
+        let input = "let f = fn(a) { let = 1; a + }; f(2)";
+
+        let lexer = Lexer::new(input);
+        let mut parser = Parser::new(lexer);
+
+        let programm = parser.parse_programm().unwrap();
+        assert_eq!(2, parser.errors().len());
+        assert_eq!(2, programm.statements.len());
+
+        if let Statements::Let(_, _, Expressions::FunctionLiteral(_, _, body, _)) =
+            &programm.statements[0]
+        {
+            assert_eq!(2, body.statements.len());
+            assert!(body
+                .statements
+                .iter()
+                .all(|s| matches!(s, Statements::Error(_))));
+        } else {
+            panic!("expected let statement with a function literal");
+        }
+        assert!(matches!(
+            programm.statements[1],
+            Statements::Expression(_, Expressions::CallExpression(_, _, _, _))
+        ));
+    }
+
     #[test]
     fn parsing_prefix_expression_test() {
-        let test_inputs = vec![("!5", "!", 5), ("-15", "-", 15)];
+        let test_inputs = [("!5", "!", 5), ("-15", "-", 15)];
 
         for inputs in test_inputs.iter() {
             let lexer = Lexer::new(inputs.0);
@@ -382,8 +1160,8 @@ mod tests {
             let statement = &program.statements[0];
             if let Statements::Expression(_, exp) = statement {
                 if let Expressions::PrefixExpression(_, op, right) = exp {
-                    assert_eq!(inputs.1, op.as_str());
-                    assert!(test_integer_literal(right.clone(), inputs.2))
+                    assert_eq!(inputs.1, op.as_ref());
+                    assert!(test_integer_literal(right, inputs.2))
                 } else {
                     panic!("Should be PrefixExpression");
                 }
@@ -395,7 +1173,7 @@ mod tests {
 
     #[test]
     fn parsing_infix_expressions_test() {
-        let test_inputs = vec![
+        let test_inputs = [
             ("5 + 5;", 5, "+", 5),
             ("5 - 5;", 5, "-", 5),
             ("5 * 5;", 5, "*", 5),
@@ -404,6 +1182,12 @@ mod tests {
             ("5 < 5;", 5, "<", 5),
             ("5 == 5;", 5, "==", 5),
             ("5 != 5;", 5, "!=", 5),
+            ("5 <= 5;", 5, "<=", 5),
+            ("5 >= 5;", 5, ">=", 5),
+            ("5 && 5;", 5, "&&", 5),
+            ("5 || 5;", 5, "||", 5),
+            ("5 % 5;", 5, "%", 5),
+            ("5 ** 5;", 5, "**", 5),
         ];
 
         for input in test_inputs.iter() {
@@ -416,20 +1200,803 @@ mod tests {
             assert_eq!(1, program.statements.len());
             let expression = &program.statements[0];
 
-            if let Statements::Expression(_, exp) = expression {
-                if let Expressions::InfixExpression(_, left, op, right) = exp {
-                    assert!(test_integer_literal(left.clone(), input.1));
-                    assert_eq!(input.2, op);
-                    assert!(test_integer_literal(right.clone(), input.3));
-                }
+            if let Statements::Expression(_, Expressions::InfixExpression(_, left, op, right)) =
+                expression
+            {
+                assert!(test_integer_literal(left, input.1));
+                assert_eq!(input.2, op);
+                assert!(test_integer_literal(right, input.3));
             }
         }
     }
 
+    #[test]
+    fn parsing_function_literal_test() {
+        let input = "fn(x, y) { x + y; }";
+
+        let lexer = Lexer::new(input);
+        let mut parser = Parser::new(lexer);
+
+        let program = parser.parse_programm().unwrap();
+        assert!(parser.errors().is_empty());
+
+        assert_eq!(1, program.statements.len());
+        if let Statements::Expression(_, Expressions::FunctionLiteral(_, parameters, body, _)) =
+            &program.statements[0]
+        {
+            let names: Vec<&str> = parameters.iter().map(|p| p.value.as_ref()).collect();
+            assert_eq!(vec!["x", "y"], names);
+            assert_eq!(1, body.statements.len());
+            if let Statements::Expression(_, Expressions::InfixExpression(_, left, op, right)) =
+                &body.statements[0]
+            {
+                assert_eq!("x", left.token_literal());
+                assert_eq!("+", op);
+                assert_eq!("y", right.token_literal());
+            } else {
+                panic!("body should be an infix expression statement");
+            }
+        } else {
+            panic!("Should be FunctionLiteral");
+        }
+    }
+
+    #[test]
+    fn parsing_function_parameters_test() {
+        let test_inputs = [
+            ("fn() {};", vec![]),
+            ("fn(x) {};", vec!["x"]),
+            ("fn(x, y, z) {};", vec!["x", "y", "z"]),
+        ];
+
+        for input in test_inputs.iter() {
+            let lexer = Lexer::new(input.0);
+            let mut parser = Parser::new(lexer);
+
+            let program = parser.parse_programm().unwrap();
+            assert!(parser.errors().is_empty());
+
+            if let Statements::Expression(_, Expressions::FunctionLiteral(_, parameters, _, _)) =
+                &program.statements[0]
+            {
+                let names: Vec<&str> = parameters.iter().map(|p| p.value.as_ref()).collect();
+                assert_eq!(input.1, names);
+            } else {
+                panic!("Should be FunctionLiteral");
+            }
+        }
+    }
+
+    #[test]
+    fn parsing_call_expression_test() {
+        let input = "add(1, 2 * 3, 4 + 5);";
+
+        let lexer = Lexer::new(input);
+        let mut parser = Parser::new(lexer);
+
+        let program = parser.parse_programm().unwrap();
+        assert!(parser.errors().is_empty());
+
+        assert_eq!(1, program.statements.len());
+        if let Statements::Expression(_, Expressions::CallExpression(_, function, arguments, _)) =
+            &program.statements[0]
+        {
+            assert_eq!("add", function.token_literal());
+            assert_eq!(3, arguments.len());
+            assert!(test_integer_literal(&arguments[0], 1));
+            assert!(matches!(
+                arguments[1],
+                Expressions::InfixExpression(_, _, _, _)
+            ));
+            assert!(matches!(
+                arguments[2],
+                Expressions::InfixExpression(_, _, _, _)
+            ));
+        } else {
+            panic!("Should be CallExpression");
+        }
+    }
+
+    #[test]
+    fn node_spans_test() {
+        let input = "let add = fn(x, y) { x + y; };\nadd(1, -2 * 3);\nreturn !true";
+
+        let lexer = Lexer::new(input);
+        let mut parser = Parser::new(lexer);
+
+        let programm = parser.parse_programm().unwrap();
+        assert!(parser.errors().is_empty());
+        assert_eq!(3, programm.statements.len());
+
+        let source = |node: &dyn Node| {
+            let span = node.span();
+            input[span.start..span.end].to_string()
+        };
+
+        assert_eq!(input, source(&programm));
+        assert_eq!(
+            "let add = fn(x, y) { x + y; }",
+            source(&programm.statements[0])
+        );
+        assert_eq!("add(1, -2 * 3)", source(&programm.statements[1]));
+        assert_eq!("return !true", source(&programm.statements[2]));
+
+        if let Statements::Let(
+            _,
+            identifier,
+            Expressions::FunctionLiteral(_, parameters, body, _),
+        ) = &programm.statements[0]
+        {
+            assert_eq!("add", source(identifier));
+            assert_eq!("y", source(&parameters[1]));
+            assert_eq!("{ x + y; }", source(body));
+            assert_eq!("x + y", source(&body.statements[0]));
+        } else {
+            panic!("expected let statement with a function literal");
+        }
+
+        if let Statements::Expression(_, Expressions::CallExpression(_, _, arguments, span)) =
+            &programm.statements[1]
+        {
+            assert_eq!((2, 1), (span.line, span.column));
+            assert_eq!("-2 * 3", source(&arguments[1]));
+            assert_eq!(
+                (2, 8),
+                (arguments[1].span().line, arguments[1].span().column)
+            );
+        } else {
+            panic!("expected call expression");
+        }
+    }
+
+    #[test]
+    fn expect_peek_error_test() {
+        let lexer = Lexer::new("let x 5;\nlet = 10;");
+        let mut parser = Parser::new(lexer);
+
+        parser.parse_programm();
+
+        let errors: Vec<(&str, usize, usize)> = parser
+            .errors()
+            .iter()
+            .map(|e| (e.0.message.as_str(), e.0.span().line, e.0.span().column))
+            .collect();
+        assert_eq!(
+            vec![
+                ("Expected Assign, found 5", 1, 7),
+                ("Expected Ident, found =", 2, 5)
+            ],
+            errors
+        );
+    }
+
+    #[test]
+    fn render_parsing_error_test() {
+        let input = "let x = 1;\nlet y 2;";
+        let lexer = Lexer::new(input);
+        let mut parser = Parser::new(lexer);
+
+        parser.parse_programm();
+
+        let expected = "error[E0001]: Expected Assign, found 2
+ --> 2:7
+  |
+2 | let y 2;
+  |       ^ expected Assign here
+";
+        assert_eq!(1, parser.errors().len());
+        assert_eq!(expected, parser.errors()[0].0.render(input, false));
+    }
+
+    #[test]
+    fn parsing_assign_expression_test() {
+        let test_inputs = [
+            ("x = 5;", "x", "=", "5"),
+            ("x += 1 + 2", "x", "+=", "1 + 2"),
+            ("x -= y", "x", "-=", "y"),
+            ("x *= f(2)", "x", "*=", "f(2)"),
+            ("x /= 2", "x", "/=", "2"),
+            ("a = b = 1", "a", "=", "b = 1"),
+            ("a = b == c", "a", "=", "b == c"),
+        ];
+
+        for input in test_inputs.iter() {
+            let lexer = Lexer::new(input.0);
+            let mut parser = Parser::new(lexer);
+
+            let programm = parser.parse_programm().unwrap();
+            assert!(parser.errors().is_empty(), "{}", input.0);
+            assert_eq!(1, programm.statements.len());
+
+            if let Statements::Expression(_, Expressions::AssignExpression(_, name, op, value)) =
+                &programm.statements[0]
+            {
+                assert_eq!(input.1, name.value);
+                assert_eq!(input.2, op);
+                let span = value.span();
+                assert_eq!(input.3, &input.0[span.start..span.end]);
+            } else {
+                panic!("Should be AssignExpression: {}", input.0);
+            }
+        }
+    }
+
+    #[test]
+    fn invalid_assign_target_test() {
+        let test_inputs = ["5 = 1", "1 + x = 2", "f() += 1", "-x = 3"];
+
+        for input in test_inputs.iter() {
+            let lexer = Lexer::new(input);
+            let mut parser = Parser::new(lexer);
+
+            parser.parse_programm();
+            assert_eq!(1, parser.errors().len(), "{}", input);
+            assert_eq!("Invalid assignment target", parser.errors()[0].0.message);
+        }
+    }
+
+    #[test]
+    fn parsing_if_expression_test() {
+        let input = "if (x < y) { x }";
+
+        let lexer = Lexer::new(input);
+        let mut parser = Parser::new(lexer);
+
+        let programm = parser.parse_programm().unwrap();
+        assert!(parser.errors().is_empty());
+        assert_eq!(1, programm.statements.len());
+
+        if let Statements::Expression(
+            _,
+            Expressions::IfExpression(_, condition, consequence, alternative),
+        ) = &programm.statements[0]
+        {
+            assert!(matches!(
+                **condition,
+                Expressions::InfixExpression(_, _, _, _)
+            ));
+            assert_eq!(1, consequence.statements.len());
+            assert_eq!("x", consequence.statements[0].token_literal());
+            assert!(alternative.is_none());
+        } else {
+            panic!("Should be IfExpression");
+        }
+    }
+
+    #[test]
+    fn parsing_if_else_expression_test() {
+        let input = "if (x < y) { x } else { y; 1 }";
+
+        let lexer = Lexer::new(input);
+        let mut parser = Parser::new(lexer);
+
+        let programm = parser.parse_programm().unwrap();
+        assert!(parser.errors().is_empty());
+
+        if let Statements::Expression(_, Expressions::IfExpression(_, _, _, Some(alternative))) =
+            &programm.statements[0]
+        {
+            assert_eq!(2, alternative.statements.len());
+            assert_eq!(input, &input[programm.span().start..programm.span().end]);
+        } else {
+            panic!("Should be IfExpression with alternative");
+        }
+    }
+
+    #[test]
+    fn parsing_else_if_chain_test() {
+        let input = "if (a) { 1 } else if (b) { 2 } else { 3 }";
+
+        let lexer = Lexer::new(input);
+        let mut parser = Parser::new(lexer);
+
+        let programm = parser.parse_programm().unwrap();
+        assert!(parser.errors().is_empty());
+        assert_eq!(1, programm.statements.len());
+
+        if let Statements::Expression(_, Expressions::IfExpression(_, _, _, Some(alternative))) =
+            &programm.statements[0]
+        {
+            assert_eq!(1, alternative.statements.len());
+            let nested = &alternative.statements[0];
+            assert_eq!(
+                "if (b) { 2 } else { 3 }",
+                &input[nested.span().start..nested.span().end]
+            );
+            assert!(matches!(
+                nested,
+                Statements::Expression(_, Expressions::IfExpression(_, _, _, Some(_)))
+            ));
+        } else {
+            panic!("Should be IfExpression with alternative");
+        }
+    }
+
+    #[test]
+    fn parsing_string_literal_test() {
+        let input = "\"hello world\"; \"a\" + \"b\"";
+
+        let lexer = Lexer::new(input);
+        let mut parser = Parser::new(lexer);
+
+        let programm = parser.parse_programm().unwrap();
+        assert!(parser.errors().is_empty());
+        assert_eq!(2, programm.statements.len());
+
+        if let Statements::Expression(_, Expressions::StringLiteral(_, value)) =
+            &programm.statements[0]
+        {
+            assert_eq!("hello world", value);
+        } else {
+            panic!("Should be StringLiteral");
+        }
+        assert!(matches!(
+            programm.statements[1],
+            Statements::Expression(_, Expressions::InfixExpression(_, _, _, _))
+        ));
+    }
+
+    #[test]
+    fn parsing_float_literal_test() {
+        let test_inputs = [
+            ("2.75", 2.75),
+            ("1e3", 1000.0),
+            ("2.5E-1", 0.25),
+            ("1_000.5", 1000.5),
+            ("1e1_0", 1e10),
+        ];
+
+        for input in test_inputs.iter() {
+            let lexer = Lexer::new(input.0);
+            let mut parser = Parser::new(lexer);
+
+            let programm = parser.parse_programm().unwrap();
+            assert!(parser.errors().is_empty(), "{}", input.0);
+
+            if let Statements::Expression(_, Expressions::FloatLiteral(token, value)) =
+                &programm.statements[0]
+            {
+                assert_eq!(input.1, *value, "{}", input.0);
+                assert_eq!(input.0, token.literal);
+            } else {
+                panic!("{} should be FloatLiteral", input.0);
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(not(feature = "bignum"))]
+    fn integer_literal_overflow_test() {
+        let input = "let max = 9223372036854775807;\nlet x = 9223372036854775808;";
+
+        let lexer = Lexer::new(input);
+        let mut parser = Parser::new(lexer);
+
+        let programm = parser.parse_programm().unwrap();
+        assert!(matches!(
+            &programm.statements[0],
+            Statements::Let(_, _, Expressions::IntegerLiteral(_, i64::MAX))
+        ));
+        assert!(matches!(programm.statements[1], Statements::Error(_)));
+
+        assert_eq!(1, parser.errors().len());
+        let error = &parser.errors()[0].0;
+        assert_eq!(
+            "Integer literal 9223372036854775808 is too large",
+            error.message
+        );
+        assert_eq!(Some("E0004".to_string()), error.code);
+        assert_eq!(Span::new(39, 58, 2, 9), error.span());
+    }
+
+    #[test]
+    #[cfg(feature = "bignum")]
+    fn big_integer_literal_test() {
+        let input = "123456789012345678901234567890; 9223372036854775807";
+
+        let lexer = Lexer::new(input);
+        let mut parser = Parser::new(lexer);
+
+        let programm = parser.parse_programm().unwrap();
+        assert!(parser.errors().is_empty());
+
+        if let Statements::Expression(_, Expressions::BigIntegerLiteral(token, value)) =
+            &programm.statements[0]
+        {
+            assert_eq!("123456789012345678901234567890", value.to_string());
+            assert_eq!(Span::new(0, 30, 1, 1), token.span);
+        } else {
+            panic!("Should be BigIntegerLiteral");
+        }
+        //Literals that fit stay plain integers
+        assert!(matches!(
+            &programm.statements[1],
+            Statements::Expression(_, Expressions::IntegerLiteral(_, i64::MAX))
+        ));
+    }
+
+    #[test]
+    fn radix_literal_test() {
+        let test_inputs = [
+            ("0x1F", 31),
+            ("0XfF", 255),
+            ("0o17", 15),
+            ("0b1010", 10),
+            ("0B1", 1),
+            ("1_000_000", 1_000_000),
+            ("0xFFFF_FFFF", 0xFFFF_FFFF),
+            ("0b1111_0000", 0b1111_0000),
+            ("0x7FFFFFFFFFFFFFFF", i64::MAX),
+            ("007", 7),
+        ];
+
+        for input in test_inputs.iter() {
+            let lexer = Lexer::new(input.0);
+            let mut parser = Parser::new(lexer);
+
+            let programm = parser.parse_programm().unwrap();
+            assert!(parser.errors().is_empty(), "{}", input.0);
+
+            match &programm.statements[0] {
+                Statements::Expression(_, Expressions::IntegerLiteral(token, value)) => {
+                    assert_eq!(input.0, token.literal, "{}", input.0);
+                    assert_eq!(input.1, *value, "{}", input.0);
+                }
+                other => panic!("{} is not an integer literal: {:?}", input.0, other),
+            }
+        }
+    }
+
+    #[test]
+    fn malformed_number_literal_test() {
+        let test_inputs = [
+            ("0x", "Missing digits after 0x", Span::new(0, 2, 1, 1)),
+            ("0b", "Missing digits after 0b", Span::new(0, 2, 1, 1)),
+            (
+                "x + 0b102",
+                "Invalid digit 2 in binary literal",
+                Span::new(8, 9, 1, 9),
+            ),
+            (
+                "0o78",
+                "Invalid digit 8 in octal literal",
+                Span::new(3, 4, 1, 4),
+            ),
+            (
+                "0x1G",
+                "Invalid digit G in hexadecimal literal",
+                Span::new(3, 4, 1, 4),
+            ),
+            ("1__000", "Misplaced digit separator", Span::new(1, 2, 1, 2)),
+            ("7_", "Misplaced digit separator", Span::new(1, 2, 1, 2)),
+            ("0x_1", "Misplaced digit separator", Span::new(2, 3, 1, 3)),
+            ("1_.5", "Misplaced digit separator", Span::new(1, 2, 1, 2)),
+            ("1.5e_3", "Misplaced digit separator", Span::new(4, 5, 1, 5)),
+        ];
+
+        for input in test_inputs.iter() {
+            let lexer = Lexer::new(input.0);
+            let mut parser = Parser::new(lexer);
+
+            parser.parse_programm();
+            assert_eq!(1, parser.errors().len(), "{}", input.0);
+            let error = &parser.errors()[0].0;
+            assert_eq!(input.1, error.message, "{}", input.0);
+            assert_eq!(input.2, error.span(), "{}", input.0);
+        }
+    }
+
+    #[test]
+    fn nesting_depth_test() {
+        let nested = |open: &str, close: &str, depth: usize| {
+            format!("{}1{}", open.repeat(depth), close.repeat(depth))
+        };
+        let test_inputs = [
+            (nested("(", ")", 10_000), Span::new(128, 129, 1, 129)),
+            (nested("-", "", 10_000), Span::new(128, 129, 1, 129)),
+            (nested("[", "]", 10_000), Span::new(128, 129, 1, 129)),
+            (nested("a(", ")", 10_000), Span::new(256, 257, 1, 257)),
+            //Chains are bounded by the depth of their tree, at the operator
+            (nested("", "+1", 10_000), Span::new(2047, 2048, 1, 2048)),
+            (nested("", "()", 10_000), Span::new(2047, 2048, 1, 2048)),
+            (nested("", "[0]", 10_000), Span::new(3070, 3071, 1, 3071)),
+            (nested("fn() {", "}", 10_000), Span::new(384, 386, 1, 385)),
+            (nested("if (x) {", "}", 10_000), Span::new(512, 514, 1, 513)),
+        ];
+
+        for input in test_inputs.iter() {
+            let mut parser = Parser::new(Lexer::new(&input.0));
+            parser.parse_programm();
+
+            assert_eq!(1, parser.errors().len(), "{}", &input.0[..10]);
+            let error = &parser.errors()[0].0;
+            assert_eq!("Expression is nested too deeply", error.message);
+            assert_eq!(Some("E0007".to_string()), error.code);
+            assert_eq!(input.1, error.span(), "{}", &input.0[..10]);
+        }
+
+        //Right below the limits, and the depths are back to zero afterwards
+        let test_inputs = [
+            nested("(", ")", 127),
+            nested("", "+1", 1023),
+            format!("[{}]", nested("", "+1", 1022)),
+        ];
+        for input in test_inputs.iter() {
+            let input = format!("{}; {}", input, input);
+            let mut parser = Parser::new(Lexer::new(&input));
+            let programm = parser.parse_programm().unwrap();
+            assert!(parser.errors().is_empty(), "{}", &input[..10]);
+            assert_eq!(2, programm.statements.len());
+        }
+
+        //Chains started inside parentheses continue the same tree
+        let input = format!(
+            "(({}){}){}",
+            nested("", "+1", 400),
+            "+1".repeat(400),
+            "+1".repeat(400)
+        );
+        let mut parser = Parser::new(Lexer::new(&input));
+        parser.parse_programm();
+        assert_eq!(1, parser.errors().len());
+        assert_eq!(Span::new(2047, 2048, 1, 2048), parser.errors()[0].0.span());
+    }
+
+    #[test]
+    fn invalid_float_literal_test() {
+        let lexer = Lexer::new("let x = 1e+;");
+        let mut parser = Parser::new(lexer);
+
+        let programm = parser.parse_programm().unwrap();
+        assert!(matches!(programm.statements[0], Statements::Error(_)));
+        assert_eq!(1, parser.errors().len());
+        assert_eq!(Some("E0006".to_string()), parser.errors()[0].0.code);
+        assert_eq!("Invalid float literal 1e+", parser.errors()[0].0.message);
+    }
+
+    #[test]
+    fn lexer_errors_are_reported_test() {
+        let input = "let s = \"a\\qb\";\nlet t = \"open";
+
+        let lexer = Lexer::new(input);
+        let mut parser = Parser::new(lexer);
+
+        let programm = parser.parse_programm().unwrap();
+        assert_eq!(2, programm.statements.len());
+
+        let errors: Vec<(&str, usize)> = parser
+            .errors()
+            .iter()
+            .map(|e| (e.0.message.as_str(), e.0.span().line))
+            .collect();
+        assert_eq!(
+            vec![
+                ("Unknown escape sequence \\q", 1),
+                ("Unterminated string literal", 2)
+            ],
+            errors
+        );
+    }
+
+    #[test]
+    fn parsing_array_literal_test() {
+        let test_inputs = [
+            ("[1, 2 * 2, 3 + 3]", 3),
+            ("[]", 0),
+            ("[[1], fn(x) { x }]", 2),
+        ];
+
+        for input in test_inputs.iter() {
+            let lexer = Lexer::new(input.0);
+            let mut parser = Parser::new(lexer);
+
+            let programm = parser.parse_programm().unwrap();
+            assert!(parser.errors().is_empty(), "{}", input.0);
+
+            if let Statements::Expression(_, Expressions::ArrayLiteral(_, elements, span)) =
+                &programm.statements[0]
+            {
+                assert_eq!(input.1, elements.len());
+                assert_eq!(input.0, &input.0[span.start..span.end]);
+            } else {
+                panic!("Should be ArrayLiteral: {}", input.0);
+            }
+        }
+    }
+
+    #[test]
+    fn parsing_index_expression_test() {
+        let input = "myArray[1 + 1]";
+
+        let lexer = Lexer::new(input);
+        let mut parser = Parser::new(lexer);
+
+        let programm = parser.parse_programm().unwrap();
+        assert!(parser.errors().is_empty());
+
+        if let Statements::Expression(_, Expressions::IndexExpression(_, left, index, span)) =
+            &programm.statements[0]
+        {
+            assert_eq!("myArray", left.token_literal());
+            assert!(matches!(**index, Expressions::InfixExpression(_, _, _, _)));
+            assert_eq!(input, &input[span.start..span.end]);
+        } else {
+            panic!("Should be IndexExpression");
+        }
+    }
+
+    #[test]
+    fn parsing_slice_expression_test() {
+        let test_inputs = [
+            ("a[1:2]", Some("1"), Some("2")),
+            ("a[:2]", None, Some("2")),
+            ("a[1:]", Some("1"), None),
+            ("a[:]", None, None),
+            ("a[-2:x + 1]", Some("-2"), Some("x + 1")),
+        ];
+
+        for input in test_inputs.iter() {
+            let lexer = Lexer::new(input.0);
+            let mut parser = Parser::new(lexer);
+
+            let programm = parser.parse_programm().unwrap();
+            assert!(parser.errors().is_empty(), "{}", input.0);
+
+            if let Statements::Expression(_, Expressions::SliceExpression(_, _, start, end, _)) =
+                &programm.statements[0]
+            {
+                let source = |e: &Option<Box<Expressions>>| {
+                    e.as_ref().map(|e| &input.0[e.span().start..e.span().end])
+                };
+                assert_eq!(input.1, source(start), "{}", input.0);
+                assert_eq!(input.2, source(end), "{}", input.0);
+            } else {
+                panic!("Should be SliceExpression: {}", input.0);
+            }
+        }
+    }
+
+    #[test]
+    fn index_precedence_test() {
+        let input = "a * [1, 2][b * c] + f(x)[0]";
+
+        let lexer = Lexer::new(input);
+        let mut parser = Parser::new(lexer);
+
+        let programm = parser.parse_programm().unwrap();
+        assert!(parser.errors().is_empty());
+
+        if let Statements::Expression(_, Expressions::InfixExpression(_, left, op, right)) =
+            &programm.statements[0]
+        {
+            assert_eq!("+", op);
+            assert!(matches!(**right, Expressions::IndexExpression(_, _, _, _)));
+            if let Expressions::InfixExpression(_, _, op, right) = &**left {
+                assert_eq!("*", op);
+                assert!(matches!(**right, Expressions::IndexExpression(_, _, _, _)));
+            } else {
+                panic!("left should be a product");
+            }
+        } else {
+            panic!("Should be InfixExpression");
+        }
+    }
+
+    #[test]
+    fn operator_precedence_test() {
+        let test_inputs = [
+            ("a || b && c", "(a || (b && c))"),
+            ("a && b || c", "((a && b) || c)"),
+            ("a == b && c != d", "((a == b) && (c != d))"),
+            ("a <= b == c >= d", "((a <= b) == (c >= d))"),
+            ("a + b % c", "(a + (b % c))"),
+            ("a * b ** c", "(a * (b ** c))"),
+            ("a ** b ** c", "(a ** (b ** c))"),
+            ("-a ** b", "(-(a ** b))"),
+            ("a ** -b", "(a ** (-b))"),
+            ("!a || b", "((!a) || b)"),
+            ("x = a || b", "x = (a || b)"),
+            ("a | b ^ c & d", "(a | (b ^ (c & d)))"),
+            ("a & b | c ^ d", "((a & b) | (c ^ d))"),
+            ("a & mask == 0", "(a & (mask == 0))"),
+            ("a && b | c", "(a && (b | c))"),
+            ("a << b + c", "(a << (b + c))"),
+            ("a < b << c", "(a < (b << c))"),
+            ("a >> b >> c", "((a >> b) >> c)"),
+            ("~a & b", "((~a) & b)"),
+            ("~-a", "(~(-a))"),
+        ];
+
+        for input in test_inputs.iter() {
+            let lexer = Lexer::new(input.0);
+            let mut parser = Parser::new(lexer);
+
+            let programm = parser.parse_programm().unwrap();
+            assert!(parser.errors().is_empty(), "{}", input.0);
+
+            if let Statements::Expression(_, expression) = &programm.statements[0] {
+                assert_eq!(input.1, parenthesize(expression), "{}", input.0);
+            } else {
+                panic!("{} is not an expression statement", input.0);
+            }
+        }
+    }
+
+    #[test]
+    fn malformed_index_test() {
+        let test_inputs = ["a[]", "a[1", "a[1:2", "[1, 2", "a[1:2:3]"];
+
+        for input in test_inputs.iter() {
+            let lexer = Lexer::new(input);
+            let mut parser = Parser::new(lexer);
+
+            parser.parse_programm();
+            assert!(!parser.errors().is_empty(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn parsing_hash_literal_test() {
+        let input = "{\"one\": 1, \"two\": 2, 3: 1 + 2, true: fn(x) { x }}";
+
+        let lexer = Lexer::new(input);
+        let mut parser = Parser::new(lexer);
+
+        let programm = parser.parse_programm().unwrap();
+        assert!(parser.errors().is_empty());
+        assert_eq!(1, programm.statements.len());
+
+        if let Statements::Expression(_, Expressions::HashLiteral(_, pairs, span)) =
+            &programm.statements[0]
+        {
+            let keys: Vec<String> = pairs.iter().map(|(k, _)| k.token_literal()).collect();
+            assert_eq!(vec!["one", "two", "3", "true"], keys);
+            assert!(test_integer_literal(&pairs[0].1, 1));
+            assert!(matches!(
+                pairs[2].1,
+                Expressions::InfixExpression(_, _, _, _)
+            ));
+            assert!(matches!(
+                pairs[3].1,
+                Expressions::FunctionLiteral(_, _, _, _)
+            ));
+            assert_eq!(input, &input[span.start..span.end]);
+        } else {
+            panic!("Should be HashLiteral");
+        }
+    }
+
+    #[test]
+    fn parsing_empty_hash_literal_test() {
+        let test_inputs = ["{}", "let h = {};", "fn() { {} }"];
+
+        for input in test_inputs.iter() {
+            let lexer = Lexer::new(input);
+            let mut parser = Parser::new(lexer);
+
+            let programm = parser.parse_programm().unwrap();
+            assert!(parser.errors().is_empty(), "{}", input);
+            assert_eq!(1, programm.statements.len(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn malformed_hash_literal_test() {
+        let test_inputs = ["{1}", "{1: }", "{1: 2 3: 4}", "{1: 2,"];
+
+        for input in test_inputs.iter() {
+            let lexer = Lexer::new(input);
+            let mut parser = Parser::new(lexer);
+
+            parser.parse_programm();
+            assert!(!parser.errors().is_empty(), "{}", input);
+        }
+    }
+
     //helper
-    fn test_integer_literal(expression: Box<Expressions>, value: usize) -> bool {
-        if let Expressions::IntegerLiteral(_, val) = *expression {
-            if value != val {
+    fn test_integer_literal(expression: &Expressions, value: i64) -> bool {
+        if let Expressions::IntegerLiteral(_, val) = expression {
+            if value != *val {
                 return false;
             }
             if !expression
@@ -444,4 +2011,23 @@ mod tests {
             false
         }
     }
+
+    //Writes operator expressions with explicit parentheses to check grouping
+    fn parenthesize(expression: &Expressions) -> String {
+        match expression {
+            Expressions::PrefixExpression(_, operator, right) => {
+                format!("({}{})", operator, parenthesize(right))
+            }
+            Expressions::InfixExpression(_, left, operator, right) => format!(
+                "({} {} {})",
+                parenthesize(left),
+                operator,
+                parenthesize(right)
+            ),
+            Expressions::AssignExpression(_, name, operator, value) => {
+                format!("{} {} {}", name.value, operator, parenthesize(value))
+            }
+            _ => expression.token_literal(),
+        }
+    }
 }
@@ -1,33 +1,43 @@
-use text_io::try_read;
+use std::{
+    cell::RefCell,
+    io::{BufRead, IsTerminal, Write},
+    rc::Rc,
+};
 
-use crate::{lexer::Lexer, token::TokenType};
+use interpreter_go::{evaluate, parse, Environment};
 const PROMPT: &str = ">>";
 
 pub fn start_repl() {
     println!("Monkey REPL v.0.1");
-    println!("To exit CTRL-C");
-    loop {
-        print!("{}", PROMPT);
+    println!("To exit CTRL-D");
+    let env = Rc::new(RefCell::new(Environment::new()));
+    let stdin = std::io::stdin();
+    let color = std::io::stdout().is_terminal();
 
-        let read_line: Result<String, text_io::Error> = try_read!("{}\n");
-
-        if let Err(x) = read_line {
-            println!("Error reading input: {:?}", x);
-            continue;
-        }
-
-        if let Ok(input) = read_line {
-            let mut lexer = Lexer::new(input.as_str());
+    print_prompt();
+    for read_line in stdin.lock().lines() {
+        let input = match read_line {
+            Ok(input) => input,
+            Err(x) => {
+                println!("Error reading input: {:?}", x);
+                break;
+            }
+        };
 
-            loop {
-                let token = lexer.next_token();
-                if token.token_type == TokenType::Eof {
-                    println!("Encountered EOF.");
-                    break;
+        match parse(&input) {
+            Ok(programm) => println!("{}", evaluate(&programm, &env)),
+            Err(errors) => {
+                for error in errors.iter() {
+                    println!("{}", error.render(&input, color));
                 }
-
-                println!("{:?}", token);
             }
         }
+
+        print_prompt();
     }
 }
+
+fn print_prompt() {
+    print!("{}", PROMPT);
+    let _ = std::io::stdout().flush();
+}
@@ -0,0 +1,322 @@
+use std::fmt;
+
+use serde::{
+    de::{self, MapAccess, SeqAccess, Visitor},
+    ser::{self, SerializeMap, SerializeSeq},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+#[cfg(feature = "bignum")]
+use crate::bignum::BigInt;
+use crate::object::{HashObject, Object};
+
+//Arrays are sequences, hashes are maps in insertion order and null is a unit.
+//Functions, builtins and errors have no data representation and fail.
+impl Serialize for Object {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Object::Integer(value) => serializer.serialize_i64(*value),
+            #[cfg(feature = "bignum")]
+            Object::BigInteger(value) => value.serialize(serializer),
+            Object::Float(value) => serializer.serialize_f64(*value),
+            Object::Boolean(value) => serializer.serialize_bool(*value),
+            Object::String(value) => serializer.serialize_str(value),
+            Object::Null => serializer.serialize_unit(),
+            Object::Array(elements) => {
+                let mut seq = serializer.serialize_seq(Some(elements.len()))?;
+                for element in elements {
+                    seq.serialize_element(element)?;
+                }
+                seq.end()
+            }
+            Object::Hash(hash) => {
+                let mut map = serializer.serialize_map(Some(hash.len()))?;
+                for (key, value) in hash.iter() {
+                    map.serialize_entry(key, value)?;
+                }
+                map.end()
+            }
+            Object::ReturnValue(value) => value.serialize(serializer),
+            Object::Error(message) => Err(ser::Error::custom(format!(
+                "cannot serialize error: {}",
+                message
+            ))),
+            Object::Function(_) | Object::Builtin(_) => Err(ser::Error::custom(format!(
+                "cannot serialize {}: {}",
+                self.type_name(),
+                self
+            ))),
+        }
+    }
+}
+
+//Formats without 128-bit integers reject values outside of 64 bits. Larger
+//values fail instead of being written as something else, a string or a tagged
+//map would read back as a different value.
+#[cfg(feature = "bignum")]
+impl Serialize for BigInt {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.to_i128() {
+            Some(value) => serializer.serialize_i128(value),
+            None => Err(ser::Error::custom(format!(
+                "cannot serialize integer {}, it does not fit in 128 bits",
+                self
+            ))),
+        }
+    }
+}
+
+//Literals in the syntax tree are written as their digits. The tree is never
+//read back, so unlike a value it cannot be mistaken for a string.
+#[cfg(feature = "bignum")]
+pub(crate) fn serialize_digits<S: Serializer>(
+    value: &BigInt,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(value)
+}
+
+impl<'de> Deserialize<'de> for Object {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(ObjectVisitor)
+    }
+}
+
+struct ObjectVisitor;
+
+impl<'de> Visitor<'de> for ObjectVisitor {
+    type Value = Object;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a number, string, boolean, null, sequence or map")
+    }
+
+    fn visit_bool<E: de::Error>(self, value: bool) -> Result<Object, E> {
+        Ok(Object::Boolean(value))
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Object, E> {
+        Ok(Object::Integer(value))
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Object, E> {
+        self.visit_i128(value as i128)
+    }
+
+    #[cfg(feature = "bignum")]
+    fn visit_i128<E: de::Error>(self, value: i128) -> Result<Object, E> {
+        Ok(Object::from(BigInt::from_i128(value)))
+    }
+
+    #[cfg(not(feature = "bignum"))]
+    fn visit_i128<E: de::Error>(self, value: i128) -> Result<Object, E> {
+        match i64::try_from(value) {
+            Ok(value) => Ok(Object::Integer(value)),
+            Err(_) => Err(E::custom(format!(
+                "integer {} does not fit in a 64-bit signed integer",
+                value
+            ))),
+        }
+    }
+
+    fn visit_u128<E: de::Error>(self, value: u128) -> Result<Object, E> {
+        match i128::try_from(value) {
+            Ok(value) => self.visit_i128(value),
+            Err(_) => Err(E::custom(format!("integer {} is too large", value))),
+        }
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<Object, E> {
+        Ok(Object::Float(value))
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Object, E> {
+        Ok(Object::String(value.to_string()))
+    }
+
+    fn visit_string<E: de::Error>(self, value: String) -> Result<Object, E> {
+        Ok(Object::String(value))
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Object, E> {
+        Ok(Object::Null)
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Object, E> {
+        Ok(Object::Null)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Object, D::Error> {
+        Object::deserialize(deserializer)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Object, A::Error> {
+        let mut elements = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(element) = seq.next_element()? {
+            elements.push(element);
+        }
+
+        Ok(Object::Array(elements))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Object, A::Error> {
+        let mut hash = HashObject::new();
+        while let Some((key, value)) = map.next_entry::<Object, Object>()? {
+            if let Err(key) = hash.insert(key, value) {
+                return Err(de::Error::custom(format!(
+                    "unusable as hash key: {}",
+                    key.type_name()
+                )));
+            }
+        }
+
+        Ok(Object::Hash(hash))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{eval::eval_programm, lexer::Lexer, parser::Parser};
+    use std::{cell::RefCell, rc::Rc};
+
+    #[test]
+    fn serialize_object_test() {
+        let test_inputs = [
+            ("1 + 2", "3"),
+            ("2.5", "2.5"),
+            ("\"a\\\"b\"", "\"a\\\"b\""),
+            ("[1, true, [], if (false) { 1 }]", "[1,true,[],null]"),
+            (
+                "{\"b\": 1, \"a\": {\"c\": [2]}}",
+                "{\"b\":1,\"a\":{\"c\":[2]}}",
+            ),
+            ("{1: \"one\"}", "{\"1\":\"one\"}"),
+        ];
+
+        for input in test_inputs.iter() {
+            let json = serde_json::to_string(&test_eval(input.0)).unwrap();
+            assert_eq!(input.1, json, "{}", input.0);
+        }
+    }
+
+    #[test]
+    fn serialize_unrepresentable_test() {
+        let test_inputs = [
+            ("fn(x) { x }", "cannot serialize FUNCTION: fn(x) { ... }"),
+            ("[int]", "cannot serialize BUILTIN: builtin int"),
+            ("{\"f\": fn() { 1 }}", "cannot serialize FUNCTION"),
+        ];
+
+        for input in test_inputs.iter() {
+            let error = serde_json::to_string(&test_eval(input.0)).unwrap_err();
+            assert!(
+                error.to_string().starts_with(input.1),
+                "{}: {}",
+                input.0,
+                error
+            );
+        }
+
+        let error = serde_json::to_string(&Object::Error("division by zero".to_string()));
+        assert_eq!(
+            "cannot serialize error: division by zero",
+            error.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn deserialize_object_test() {
+        let json = r#"{"name": "packet", "size": 12, "ratio": 0.5, "flags": [true, null]}"#;
+        let object: Object = serde_json::from_str(json).unwrap();
+
+        let mut expected = HashObject::new();
+        for (key, value) in [
+            ("name", Object::String("packet".to_string())),
+            ("size", Object::Integer(12)),
+            ("ratio", Object::Float(0.5)),
+            (
+                "flags",
+                Object::Array(vec![Object::Boolean(true), Object::Null]),
+            ),
+        ] {
+            expected
+                .insert(Object::String(key.to_string()), value)
+                .unwrap();
+        }
+        assert_eq!(Object::Hash(expected), object);
+
+        //Round trip through a script
+        let env = Rc::new(RefCell::new(crate::environment::Environment::new()));
+        env.borrow_mut().set("payload", object);
+        let result = eval_programm(&parse("payload[\"size\"] * 2"), &env);
+        assert_eq!("24", serde_json::to_string(&result).unwrap());
+    }
+
+    #[cfg(not(feature = "bignum"))]
+    #[test]
+    fn deserialize_large_integer_test() {
+        let error = serde_json::from_str::<Object>("18446744073709551615").unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("integer 18446744073709551615 does not fit"));
+    }
+
+    #[cfg(feature = "bignum")]
+    #[test]
+    fn big_integer_serde_test() {
+        let object: Object = serde_json::from_str("18446744073709551615").unwrap();
+        assert_eq!("18446744073709551615", object.to_string());
+        assert_eq!(
+            "18446744073709551615",
+            serde_json::to_string(&object).unwrap()
+        );
+        assert_eq!(
+            r#"{"18446744073709551616":1}"#,
+            serde_json::to_string(&test_eval("{2 ** 64: 1}")).unwrap()
+        );
+
+        let error = serde_json::to_string(&test_eval("-(2 ** 200)")).unwrap_err();
+        assert_eq!(
+            "cannot serialize integer -1606938044258990275541962092341162602522202993782792835301376, it does not fit in 128 bits",
+            error.to_string()
+        );
+
+        //Hashes stay hashes whatever their keys
+        let object = test_eval("{\"bigint\": \"123456789012345678901234567890\"}");
+        let json = serde_json::to_string(&object).unwrap();
+        assert_eq!(object, serde_json::from_str::<Object>(&json).unwrap());
+
+        let programm = parse("123456789012345678901234567890123456789012");
+        let json = serde_json::to_value(&programm).unwrap();
+        assert_eq!(
+            "123456789012345678901234567890123456789012",
+            json["statements"][0]["Expression"][1]["BigIntegerLiteral"][1]
+        );
+    }
+
+    #[test]
+    fn serialize_ast_test() {
+        let programm = parse("let x = 1;");
+        let json = serde_json::to_value(&programm).unwrap();
+
+        let statement = &json["statements"][0]["Let"];
+        assert_eq!("Let", statement[0]["token_type"]);
+        assert_eq!("x", statement[1]["value"]);
+        assert_eq!(1, statement[2]["IntegerLiteral"][1]);
+        assert_eq!(8, statement[2]["IntegerLiteral"][0]["span"]["start"]);
+    }
+
+    //helper
+    fn parse(input: &str) -> crate::ast::Programm<'_> {
+        let mut parser = Parser::new(Lexer::new(input));
+        let programm = parser.parse_programm().unwrap();
+        assert!(parser.errors().is_empty(), "{}", input);
+        programm
+    }
+
+    fn test_eval(input: &str) -> Object {
+        let env = Rc::new(RefCell::new(crate::environment::Environment::new()));
+        eval_programm(&parse(input), &env)
+    }
+}
@@ -1,63 +1,541 @@
-use crate::token::Token;
+use std::{borrow::Cow, cell::OnceCell, fmt::Debug, rc::Rc};
+
+#[cfg(feature = "bignum")]
+use crate::bignum::BigInt;
+use crate::{span::Span, token::Token};
 
 #[derive(Debug, PartialEq, Clone)]
-pub enum Statements {
-    Let(Token, Identifier, Expressions),
-    Return(Token, Expressions),
-    Expression(Token, Expressions),
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Statements<'src> {
+    Let(Token<'src>, Identifier<'src>, Expressions<'src>),
+    Return(Token<'src>, Expressions<'src>),
+    Expression(Token<'src>, Expressions<'src>),
+    //Source that could not be parsed
+    Error(Span),
 }
 
-//Placeholder Expressions
 #[derive(Debug, PartialEq, Clone)]
-pub enum Expressions {
-    Variant1,
-    IntegerLiteral(Token, usize),
-    PrefixExpression(Token, String, Box<Expressions>),
-    InfixExpression(Token, Box<Expressions>, String, Box<Expressions>),
-    BooleanExpression(Token, bool),
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Expressions<'src> {
+    IntegerLiteral(Token<'src>, i64),
+    //Literals too large for an i64
+    #[cfg(feature = "bignum")]
+    BigIntegerLiteral(
+        Token<'src>,
+        #[cfg_attr(
+            feature = "serde",
+            serde(serialize_with = "crate::serialize::serialize_digits")
+        )]
+        BigInt,
+    ),
+    FloatLiteral(Token<'src>, f64),
+    StringLiteral(Token<'src>, Cow<'src, str>),
+    PrefixExpression(Token<'src>, Cow<'src, str>, Box<Expressions<'src>>),
+    InfixExpression(
+        Token<'src>,
+        Box<Expressions<'src>>,
+        Cow<'src, str>,
+        Box<Expressions<'src>>,
+    ),
+    BooleanExpression(Token<'src>, bool),
+    Identifier(Identifier<'src>),
+    FunctionLiteral(
+        Token<'src>,
+        Vec<Identifier<'src>>,
+        BlockStatement<'src>,
+        #[cfg_attr(feature = "serde", serde(skip))] FunctionCache,
+    ),
+    CallExpression(
+        Token<'src>,
+        Box<Expressions<'src>>,
+        Vec<Expressions<'src>>,
+        Span,
+    ),
+    AssignExpression(
+        Token<'src>,
+        Identifier<'src>,
+        Cow<'src, str>,
+        Box<Expressions<'src>>,
+    ),
+    //An `else if` is stored as an alternative block holding the nested if
+    IfExpression(
+        Token<'src>,
+        Box<Expressions<'src>>,
+        BlockStatement<'src>,
+        Option<BlockStatement<'src>>,
+    ),
+    ArrayLiteral(Token<'src>, Vec<Expressions<'src>>, Span),
+    //Key value pairs in source order
+    HashLiteral(
+        Token<'src>,
+        Vec<(Expressions<'src>, Expressions<'src>)>,
+        Span,
+    ),
+    IndexExpression(
+        Token<'src>,
+        Box<Expressions<'src>>,
+        Box<Expressions<'src>>,
+        Span,
+    ),
+    //Both bounds are optional as in `arr[:2]` or `arr[1:]`
+    SliceExpression(
+        Token<'src>,
+        Box<Expressions<'src>>,
+        Option<Box<Expressions<'src>>>,
+        Option<Box<Expressions<'src>>>,
+        Span,
+    ),
+}
+
+//Owned conversions, used to keep parts of the tree around longer than the
+//source they were parsed from, e.g. function bodies captured at runtime. They
+//work by reference, as a function body is copied out of a tree that is still
+//being evaluated.
+impl Statements<'_> {
+    pub fn to_static(&self) -> Statements<'static> {
+        match self {
+            Statements::Let(token, name, value) => owned_let(token, name, value),
+            Statements::Return(token, value) => owned_statement(token, value, Statements::Return),
+            Statements::Expression(token, expression) => {
+                owned_statement(token, expression, Statements::Expression)
+            }
+            Statements::Error(span) => Statements::Error(*span),
+        }
+    }
+
+    pub fn into_owned(self) -> Statements<'static> {
+        self.to_static()
+    }
+}
+
+impl Expressions<'_> {
+    pub fn to_static(&self) -> Expressions<'static> {
+        match self.chained_operand() {
+            Some(_) => self.chain_to_static(),
+            None => self.node_to_static(None),
+        }
+    }
+
+    pub fn into_owned(self) -> Expressions<'static> {
+        self.to_static()
+    }
+
+    fn chained_operand(&self) -> Option<&Self> {
+        match self {
+            Expressions::InfixExpression(_, left, _, _)
+            | Expressions::CallExpression(_, left, _, _)
+            | Expressions::IndexExpression(_, left, _, _)
+            | Expressions::SliceExpression(_, left, _, _, _) => Some(left),
+            _ => None,
+        }
+    }
+
+    //Chains like `a + b + c` nest to the left as deep as they are long, their
+    //links are converted in a loop so only the other operands recurse
+    fn chain_to_static(&self) -> Expressions<'static> {
+        let mut links = vec![];
+        let mut first = self;
+        while let Some(left) = first.chained_operand() {
+            links.push(first);
+            first = left;
+        }
+
+        let mut owned = Box::new(first.node_to_static(None));
+        for link in links.into_iter().rev() {
+            owned = Box::new(link.node_to_static(Some(owned)));
+        }
+
+        *owned
+    }
+
+    //Only dispatches, like the evaluator, to keep the frames of the recursion
+    //small. Links of a chain can be given their left operand converted.
+    fn node_to_static(&self, left: Option<Box<Expressions<'static>>>) -> Expressions<'static> {
+        let operand = |operand: &Self| left.unwrap_or_else(|| Box::new(operand.to_static()));
+
+        match self {
+            Expressions::IntegerLiteral(token, value) => {
+                owned_leaf(token, *value, Expressions::IntegerLiteral)
+            }
+            #[cfg(feature = "bignum")]
+            Expressions::BigIntegerLiteral(token, value) => {
+                owned_leaf(token, value.clone(), Expressions::BigIntegerLiteral)
+            }
+            Expressions::FloatLiteral(token, value) => {
+                owned_leaf(token, *value, Expressions::FloatLiteral)
+            }
+            Expressions::StringLiteral(token, value) => {
+                owned_leaf(token, owned(value), Expressions::StringLiteral)
+            }
+            Expressions::PrefixExpression(token, operator, right) => {
+                owned_prefix(token, operator, right)
+            }
+            Expressions::InfixExpression(token, left, operator, right) => {
+                owned_infix(token, operand(left), operator, right)
+            }
+            Expressions::BooleanExpression(token, value) => {
+                owned_leaf(token, *value, Expressions::BooleanExpression)
+            }
+            Expressions::Identifier(identifier) => owned_identifier(identifier),
+            Expressions::FunctionLiteral(token, parameters, body, cache) => {
+                owned_function(token, parameters, body, cache)
+            }
+            Expressions::CallExpression(token, function, arguments, span) => {
+                owned_call(token, operand(function), arguments, *span)
+            }
+            Expressions::AssignExpression(token, name, operator, value) => {
+                owned_assign(token, name, operator, value)
+            }
+            Expressions::IfExpression(token, condition, consequence, alternative) => {
+                owned_if(token, condition, consequence, alternative.as_ref())
+            }
+            Expressions::ArrayLiteral(token, elements, span) => owned_array(token, elements, *span),
+            Expressions::HashLiteral(token, pairs, span) => owned_hash(token, pairs, *span),
+            Expressions::IndexExpression(token, left, index, span) => {
+                owned_index(token, operand(left), index, *span)
+            }
+            Expressions::SliceExpression(token, left, start, end, span) => owned_slice(
+                token,
+                operand(left),
+                start.as_deref(),
+                end.as_deref(),
+                *span,
+            ),
+        }
+    }
 }
 
 pub trait Node {
     fn token_literal(&self) -> String;
+    fn span(&self) -> Span;
 }
 
-impl Node for Statements {
+impl Node for Statements<'_> {
     fn token_literal(&self) -> String {
         match self {
-            Statements::Let(token, _, _) => token.literal.to_owned(),
-            Statements::Return(token, _) => token.literal.to_owned(),
-            Statements::Expression(token, _) => token.literal.to_owned(),
+            Statements::Let(token, _, _) => token.literal.to_string(),
+            Statements::Return(token, _) => token.literal.to_string(),
+            Statements::Expression(token, _) => token.literal.to_string(),
+            Statements::Error(_) => "".to_string(),
+        }
+    }
+
+    fn span(&self) -> Span {
+        match self {
+            Statements::Let(token, _, value) => token.span.to(value.span()),
+            Statements::Return(token, value) => token.span.to(value.span()),
+            Statements::Expression(_, expression) => expression.span(),
+            Statements::Error(span) => *span,
         }
     }
 }
 
-impl Node for Expressions {
+impl Node for Expressions<'_> {
     fn token_literal(&self) -> String {
         match self {
-            Expressions::Variant1 => todo!(),
-            Expressions::IntegerLiteral(token, _) => token.literal.to_owned(),
-            Expressions::PrefixExpression(token, _, _) => token.literal.to_owned(),
-            Expressions::InfixExpression(token, _, _, _) => token.literal.to_owned(),
-            Expressions::BooleanExpression(token, _) => token.literal.to_owned(),
+            Expressions::IntegerLiteral(token, _) => token.literal.to_string(),
+            #[cfg(feature = "bignum")]
+            Expressions::BigIntegerLiteral(token, _) => token.literal.to_string(),
+            Expressions::FloatLiteral(token, _) => token.literal.to_string(),
+            Expressions::StringLiteral(token, _) => token.literal.to_string(),
+            Expressions::PrefixExpression(token, _, _) => token.literal.to_string(),
+            Expressions::InfixExpression(token, _, _, _) => token.literal.to_string(),
+            Expressions::BooleanExpression(token, _) => token.literal.to_string(),
+            Expressions::Identifier(identifier) => identifier.token_literal(),
+            Expressions::FunctionLiteral(token, _, _, _) => token.literal.to_string(),
+            Expressions::CallExpression(token, _, _, _) => token.literal.to_string(),
+            Expressions::AssignExpression(token, _, _, _) => token.literal.to_string(),
+            Expressions::IfExpression(token, _, _, _) => token.literal.to_string(),
+            Expressions::ArrayLiteral(token, _, _) => token.literal.to_string(),
+            Expressions::HashLiteral(token, _, _) => token.literal.to_string(),
+            Expressions::IndexExpression(token, _, _, _) => token.literal.to_string(),
+            Expressions::SliceExpression(token, _, _, _, _) => token.literal.to_string(),
+        }
+    }
+
+    fn span(&self) -> Span {
+        match self {
+            Expressions::IntegerLiteral(token, _) => token.span,
+            #[cfg(feature = "bignum")]
+            Expressions::BigIntegerLiteral(token, _) => token.span,
+            Expressions::FloatLiteral(token, _) => token.span,
+            Expressions::StringLiteral(token, _) => token.span,
+            Expressions::PrefixExpression(token, _, right) => token.span.to(right.span()),
+            Expressions::InfixExpression(_, left, _, right) => left.span().to(right.span()),
+            Expressions::BooleanExpression(token, _) => token.span,
+            Expressions::Identifier(identifier) => identifier.span(),
+            Expressions::FunctionLiteral(token, _, body, _) => token.span.to(body.span),
+            Expressions::CallExpression(_, _, _, span) => *span,
+            Expressions::AssignExpression(_, name, _, value) => name.span().to(value.span()),
+            Expressions::IfExpression(token, _, consequence, alternative) => match alternative {
+                Some(alternative) => token.span.to(alternative.span),
+                None => token.span.to(consequence.span),
+            },
+            Expressions::ArrayLiteral(_, _, span) => *span,
+            Expressions::HashLiteral(_, _, span) => *span,
+            Expressions::IndexExpression(_, _, _, span) => *span,
+            Expressions::SliceExpression(_, _, _, _, span) => *span,
         }
     }
 }
 
 #[derive(Debug, PartialEq, Clone)]
-pub struct Identifier {
-    pub token: Token,
-    pub value: String,
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Identifier<'src> {
+    pub token: Token<'src>,
+    pub value: Cow<'src, str>,
 }
 
-impl Node for Identifier {
+impl Identifier<'_> {
+    pub fn to_static(&self) -> Identifier<'static> {
+        Identifier {
+            token: owned_token(&self.token),
+            value: owned(&self.value),
+        }
+    }
+
+    pub fn into_owned(self) -> Identifier<'static> {
+        self.to_static()
+    }
+}
+
+impl Node for Identifier<'_> {
     fn token_literal(&self) -> String {
-        self.token.literal.to_owned()
+        self.token.literal.to_string()
+    }
+
+    fn span(&self) -> Span {
+        self.token.span
     }
 }
 
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct BlockStatement<'src> {
+    pub token: Token<'src>,
+    pub statements: Vec<Statements<'src>>,
+    //From the opening to the closing brace
+    pub span: Span,
+}
+
+impl BlockStatement<'_> {
+    pub fn to_static(&self) -> BlockStatement<'static> {
+        BlockStatement {
+            token: owned_token(&self.token),
+            statements: self.statements.iter().map(Statements::to_static).collect(),
+            span: self.span,
+        }
+    }
+
+    pub fn into_owned(self) -> BlockStatement<'static> {
+        self.to_static()
+    }
+}
+
+impl Node for BlockStatement<'_> {
+    fn token_literal(&self) -> String {
+        self.token.literal.to_string()
+    }
+
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+//helper
+//Owned parameters and body of a function literal, made the first time the
+//literal is evaluated and shared by every function value created from it.
+//Not part of the tree, it is equal to any other cache and never serialized.
+#[derive(Clone, Default)]
+pub struct FunctionCache(OnceCell<(Rc<[Identifier<'static>]>, Rc<BlockStatement<'static>>)>);
+
+impl FunctionCache {
+    pub fn get_or_init(
+        &self,
+        parameters: &[Identifier],
+        body: &BlockStatement,
+    ) -> &(Rc<[Identifier<'static>]>, Rc<BlockStatement<'static>>) {
+        self.0.get_or_init(|| {
+            (
+                parameters.iter().map(Identifier::to_static).collect(),
+                Rc::new(body.to_static()),
+            )
+        })
+    }
+}
+
+impl PartialEq for FunctionCache {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl Debug for FunctionCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("FunctionCache").finish()
+    }
+}
+
+fn owned(value: &str) -> Cow<'static, str> {
+    Cow::Owned(value.to_string())
+}
+
+fn owned_token(token: &Token) -> Token<'static> {
+    token.clone().into_owned()
+}
+
+fn owned_list(expressions: &[Expressions]) -> Vec<Expressions<'static>> {
+    expressions.iter().map(Expressions::to_static).collect()
+}
+
+//One function per variant, see node_to_static
+fn owned_let(token: &Token, name: &Identifier, value: &Expressions) -> Statements<'static> {
+    Statements::Let(owned_token(token), name.to_static(), value.to_static())
+}
+
+fn owned_statement(
+    token: &Token,
+    expression: &Expressions,
+    variant: fn(Token<'static>, Expressions<'static>) -> Statements<'static>,
+) -> Statements<'static> {
+    variant(owned_token(token), expression.to_static())
+}
+
+fn owned_leaf<T>(
+    token: &Token,
+    value: T,
+    variant: fn(Token<'static>, T) -> Expressions<'static>,
+) -> Expressions<'static> {
+    variant(owned_token(token), value)
+}
+
+fn owned_identifier(identifier: &Identifier) -> Expressions<'static> {
+    Expressions::Identifier(identifier.to_static())
+}
+
+fn owned_prefix(token: &Token, operator: &str, right: &Expressions) -> Expressions<'static> {
+    Expressions::PrefixExpression(
+        owned_token(token),
+        owned(operator),
+        Box::new(right.to_static()),
+    )
+}
+
+fn owned_infix(
+    token: &Token,
+    left: Box<Expressions<'static>>,
+    operator: &str,
+    right: &Expressions,
+) -> Expressions<'static> {
+    Expressions::InfixExpression(
+        owned_token(token),
+        left,
+        owned(operator),
+        Box::new(right.to_static()),
+    )
+}
+
+//A filled cache is carried over, an empty one is filled separately for the
+//copy
+fn owned_function(
+    token: &Token,
+    parameters: &[Identifier],
+    body: &BlockStatement,
+    cache: &FunctionCache,
+) -> Expressions<'static> {
+    Expressions::FunctionLiteral(
+        owned_token(token),
+        parameters.iter().map(Identifier::to_static).collect(),
+        body.to_static(),
+        cache.clone(),
+    )
+}
+
+fn owned_call(
+    token: &Token,
+    function: Box<Expressions<'static>>,
+    arguments: &[Expressions],
+    span: Span,
+) -> Expressions<'static> {
+    Expressions::CallExpression(owned_token(token), function, owned_list(arguments), span)
+}
+
+fn owned_assign(
+    token: &Token,
+    name: &Identifier,
+    operator: &str,
+    value: &Expressions,
+) -> Expressions<'static> {
+    Expressions::AssignExpression(
+        owned_token(token),
+        name.to_static(),
+        owned(operator),
+        Box::new(value.to_static()),
+    )
+}
+
+fn owned_if(
+    token: &Token,
+    condition: &Expressions,
+    consequence: &BlockStatement,
+    alternative: Option<&BlockStatement>,
+) -> Expressions<'static> {
+    Expressions::IfExpression(
+        owned_token(token),
+        Box::new(condition.to_static()),
+        consequence.to_static(),
+        alternative.map(BlockStatement::to_static),
+    )
+}
+
+fn owned_array(token: &Token, elements: &[Expressions], span: Span) -> Expressions<'static> {
+    Expressions::ArrayLiteral(owned_token(token), owned_list(elements), span)
+}
+
+fn owned_hash(
+    token: &Token,
+    pairs: &[(Expressions, Expressions)],
+    span: Span,
+) -> Expressions<'static> {
+    Expressions::HashLiteral(
+        owned_token(token),
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_static(), value.to_static()))
+            .collect(),
+        span,
+    )
+}
+
+fn owned_index(
+    token: &Token,
+    left: Box<Expressions<'static>>,
+    index: &Expressions,
+    span: Span,
+) -> Expressions<'static> {
+    Expressions::IndexExpression(owned_token(token), left, Box::new(index.to_static()), span)
+}
+
+fn owned_slice(
+    token: &Token,
+    left: Box<Expressions<'static>>,
+    start: Option<&Expressions>,
+    end: Option<&Expressions>,
+    span: Span,
+) -> Expressions<'static> {
+    Expressions::SliceExpression(
+        owned_token(token),
+        left,
+        start.map(|start| Box::new(start.to_static())),
+        end.map(|end| Box::new(end.to_static())),
+        span,
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{
+        ast::Programm,
         lexer::Lexer,
         parser::Parser,
         statements::{Expressions, Node, Statements},
@@ -108,4 +586,25 @@ mod tests {
             panic!("statement is not an expression statement");
         }
     }
+
+    #[test]
+    fn into_owned_test() {
+        let owned = {
+            let input =
+                String::from("let f = fn(x) { if (x > 1) { x[1:] } else { {\"a\\n\": [x]} } };");
+            let lexer = Lexer::new(&input);
+            let mut parser = Parser::new(lexer);
+
+            let programm = parser.parse_programm().unwrap();
+            assert_eq!(0, parser.errors().len());
+            let expected = format!("{:?}", programm.statements);
+
+            let owned: Programm<'static> = programm.into_owned();
+            assert_eq!(expected, format!("{:?}", owned.statements));
+            owned
+        };
+
+        assert_eq!(1, owned.statements.len());
+        assert_eq!("let", owned.token_literal());
+    }
 }
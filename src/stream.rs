@@ -0,0 +1,259 @@
+use std::{collections::VecDeque, io::Read};
+
+use crate::{
+    diagnostic::Diagnostic,
+    lexer::Lexer,
+    token::{Token, TokenType},
+};
+
+const CHUNK_SIZE: usize = 64 * 1024;
+
+//Lexes a source read from a reader in chunks, so only the unlexed tail of the
+//input is held in memory. Tokens are owned as the buffer they were lexed from
+//is reused.
+//
+//A token ending at or one character before the end of the buffer may change
+//with the next chunk (`=` followed by `=`, `1.` followed by `5`, an
+//identifier, an unterminated string or comment), so it is lexed again
+//together with its leading trivia once more input arrived. At least as much
+//input as is buffered is read before that, so a token far larger than a chunk
+//is lexed a few times instead of once per chunk.
+#[derive(Debug)]
+pub struct StreamLexer<R> {
+    reader: R,
+    chunk_size: usize,
+    //Unlexed input, starting at offset, line and column of the whole source
+    buffer: String,
+    offset: usize,
+    line: usize,
+    column: usize,
+    //Bytes of a UTF-8 character split between two reads
+    incomplete: Vec<u8>,
+    reader_done: bool,
+    finished: bool,
+    tokens: VecDeque<Token<'static>>,
+    errors: Vec<Diagnostic>,
+}
+
+impl<R: Read> StreamLexer<R> {
+    pub fn new(reader: R) -> Self {
+        StreamLexer::with_chunk_size(reader, CHUNK_SIZE)
+    }
+
+    pub fn with_chunk_size(reader: R, chunk_size: usize) -> Self {
+        StreamLexer {
+            reader,
+            chunk_size: chunk_size.max(1),
+            buffer: String::new(),
+            offset: 0,
+            line: 1,
+            column: 1,
+            incomplete: vec![],
+            reader_done: false,
+            finished: false,
+            tokens: VecDeque::new(),
+            errors: vec![],
+        }
+    }
+
+    pub fn errors(&self) -> &[Diagnostic] {
+        &self.errors
+    }
+
+    pub fn take_errors(&mut self) -> Vec<Diagnostic> {
+        std::mem::take(&mut self.errors)
+    }
+
+    //Same as Lexer::next_token, except that Eof is only returned once the
+    //reader is exhausted
+    pub fn next_token(&mut self) -> std::io::Result<Token<'static>> {
+        loop {
+            if let Some(token) = self.tokens.pop_front() {
+                return Ok(token);
+            }
+
+            self.lex_buffer();
+            if self.tokens.is_empty() {
+                let wanted = self.buffer.len() * 2;
+                self.read_chunk()?;
+                while !self.reader_done && self.buffer.len() < wanted {
+                    self.read_chunk()?;
+                }
+            }
+        }
+    }
+
+    //Moves every token that cannot change with more input from the buffer into
+    //the queue
+    fn lex_buffer(&mut self) {
+        let mut lexer = Lexer::with_position(&self.buffer, self.offset, self.line, self.column);
+        let mut consumed = None;
+
+        loop {
+            let token = lexer.next_token();
+            //The lexer looks up to two characters past a token, for `1.5`
+            let rest = &self.buffer[token.span.end - self.offset..];
+            let near_buffer_end = rest.chars().nth(1).is_none();
+
+            if !self.reader_done && near_buffer_end {
+                //Comments in front of the token may be unfinished as well
+                consumed = Some(lexer.token_start());
+                break;
+            }
+
+            self.errors.extend(lexer.take_errors());
+            let is_eof = token.token_type == TokenType::Eof;
+            self.tokens.push_back(token.into_owned());
+            if is_eof {
+                break;
+            }
+        }
+
+        //Errors of the unfinished token are reported when it is lexed again
+        if let Some(span) = consumed {
+            self.buffer.drain(..span.start - self.offset);
+            self.offset = span.start;
+            self.line = span.line;
+            self.column = span.column;
+        } else {
+            self.buffer.clear();
+        }
+    }
+
+    fn read_chunk(&mut self) -> std::io::Result<()> {
+        let mut chunk = vec![0; self.chunk_size];
+        let read = loop {
+            match self.reader.read(&mut chunk) {
+                Ok(read) => break read,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        };
+
+        if read == 0 {
+            self.reader_done = true;
+            //A character cut off by the end of the input
+            if !self.incomplete.is_empty() {
+                self.buffer
+                    .push_str(&String::from_utf8_lossy(&self.incomplete));
+                self.incomplete.clear();
+            }
+            return Ok(());
+        }
+
+        self.incomplete.extend_from_slice(&chunk[..read]);
+        let valid = match std::str::from_utf8(&self.incomplete) {
+            Ok(text) => text.len(),
+            //error_len is None if the bytes end in the middle of a character
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            Err(e) => {
+                return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, e));
+            }
+        };
+
+        let rest = self.incomplete.split_off(valid);
+        let text = std::mem::replace(&mut self.incomplete, rest);
+        //Only valid UTF-8 is left in text at this point
+        self.buffer
+            .push_str(std::str::from_utf8(&text).expect("checked above"));
+        Ok(())
+    }
+}
+
+//Yields every token up to and including Eof, or the first read error
+impl<R: Read> Iterator for StreamLexer<R> {
+    type Item = std::io::Result<Token<'static>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        let token = self.next_token();
+        self.finished = match &token {
+            Ok(token) => token.token_type == TokenType::Eof,
+            Err(_) => true,
+        };
+        Some(token)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::tokenize;
+
+    #[test]
+    fn stream_matches_lexer_test() {
+        let input = "let s = \"日本 \\u{1F600}\"; let add = fn(x, y) { x + y; };
+        if (add(1, 2) != 3) { return [1, 2][0:1]; } else { x += 10 == 10 }
+        {\"key\": true} ü // line comment
+        1.5 + 2.25e10 * 3E-2 - 4.e 5. 6_000.5e+3 0x1F 7.x
+        /* block /* nested */ comment */ 1 / 2 /= 3 //";
+
+        let expected = tokenize(input);
+
+        for chunk_size in 1..=input.len() {
+            let tokens: Vec<Token> = StreamLexer::with_chunk_size(input.as_bytes(), chunk_size)
+                .collect::<std::io::Result<_>>()
+                .unwrap();
+
+            assert_eq!(expected, tokens, "chunk size {}", chunk_size);
+        }
+    }
+
+    #[test]
+    fn stream_errors_test() {
+        let input = "\"a\\q\" \"open";
+
+        let mut lexer = Lexer::new(input);
+        while lexer.next_token().token_type != TokenType::Eof {}
+        let expected = lexer.take_errors();
+
+        for chunk_size in [1, 4, 1024] {
+            let mut stream = StreamLexer::with_chunk_size(input.as_bytes(), chunk_size);
+            while stream.next_token().unwrap().token_type != TokenType::Eof {}
+
+            assert_eq!(expected, stream.take_errors(), "chunk size {}", chunk_size);
+        }
+    }
+
+    #[test]
+    fn stream_invalid_utf8_test() {
+        let input: &[u8] = b"let x = \xff;";
+        let mut stream = StreamLexer::new(input);
+
+        let error = stream.find_map(|token| token.err()).unwrap();
+        assert_eq!(std::io::ErrorKind::InvalidData, error.kind());
+        assert!(stream.next().is_none());
+    }
+
+    //Run with `cargo test --release -- --ignored --nocapture` to see the numbers
+    #[test]
+    #[ignore]
+    fn stream_scales_linearly_benchmark() {
+        let mut timings = vec![];
+
+        for megabytes in [1, 2, 4, 8] {
+            let input = format!("/*{}*/ 1", "x".repeat(megabytes * 1024 * 1024));
+            let start = std::time::Instant::now();
+
+            let tokens = StreamLexer::new(input.as_bytes())
+                .collect::<std::io::Result<Vec<_>>>()
+                .unwrap();
+
+            let elapsed = start.elapsed();
+            println!(
+                "{} MB comment: {} tokens in {:?}",
+                megabytes,
+                tokens.len(),
+                elapsed
+            );
+            timings.push(elapsed.as_secs_f64());
+        }
+
+        //A token lexed again for every chunk would make this 64x
+        let ratio = timings[3] / timings[0];
+        assert!(ratio < 16.0, "lexing time grew {:.1}x for 8x input", ratio);
+    }
+}
@@ -1,4 +1,9 @@
+use std::borrow::Cow;
+
+use crate::span::Span;
+
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum TokenType {
     Illegal,
     Eof,
@@ -6,17 +11,37 @@ pub enum TokenType {
     //Identifiers and literals
     Ident,
     Int,
+    Float,
+    String,
 
     //Operators
     Assign,
+    PlusAssign,
+    MinusAssign,
+    AsteriskAssign,
+    SlashAssign,
     Plus,
     Minus,
     Bang,
     Asterisk,
     Slash,
+    Percent,
+    Power,
+
+    Ampersand,
+    Pipe,
+    Caret,
+    Tilde,
+    ShiftLeft,
+    ShiftRight,
 
     Lt,
     Gt,
+    LtEquals,
+    GtEquals,
+
+    And,
+    Or,
 
     Equals,
     NotEquals,
@@ -24,11 +49,14 @@ pub enum TokenType {
     //Delimiters
     Comma,
     Semicolon,
+    Colon,
 
     LParen,
     RParen,
     LBrace,
     RBrace,
+    LBracket,
+    RBracket,
 
     //Keywords
     Function,
@@ -40,8 +68,68 @@ pub enum TokenType {
     Return,
 }
 
+//The literal borrows from the source, only strings with escape sequences need
+//an allocation for their resolved value
 #[derive(Debug, Clone, PartialEq)]
-pub struct Token {
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Token<'src> {
     pub token_type: TokenType,
-    pub literal: String,
+    pub literal: Cow<'src, str>,
+    pub span: Span,
+    //Only filled if the lexer keeps trivia, see Lexer::with_trivia
+    pub leading_trivia: Vec<Trivia<'src>>,
+    pub trailing_trivia: Vec<Trivia<'src>>,
+}
+
+impl Token<'_> {
+    //Detaches the token from the source it was lexed from
+    pub fn into_owned(self) -> Token<'static> {
+        Token {
+            token_type: self.token_type,
+            literal: Cow::Owned(self.literal.into_owned()),
+            span: self.span,
+            leading_trivia: self
+                .leading_trivia
+                .into_iter()
+                .map(Trivia::into_owned)
+                .collect(),
+            trailing_trivia: self
+                .trailing_trivia
+                .into_iter()
+                .map(Trivia::into_owned)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum TriviaKind {
+    //Spaces and tabs
+    Whitespace,
+    //`\n` or `\r\n`
+    Newline,
+    //From `//` up to the end of the line
+    LineComment,
+    //`/* */`, possibly nested
+    BlockComment,
+}
+
+//Source text between tokens, kept so tools can reproduce the source exactly
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Trivia<'src> {
+    pub kind: TriviaKind,
+    pub text: Cow<'src, str>,
+    pub span: Span,
+}
+
+impl Trivia<'_> {
+    pub fn into_owned(self) -> Trivia<'static> {
+        Trivia {
+            kind: self.kind,
+            text: Cow::Owned(self.text.into_owned()),
+            span: self.span,
+        }
+    }
 }
@@ -0,0 +1,224 @@
+use std::fmt::Display;
+
+#[cfg(feature = "bignum")]
+use crate::bignum::BigInt;
+use crate::object::Object;
+
+//Conversion of a Monkey value into a Rust type, used for the arguments of
+//host functions. The error describes what was expected, like
+//"expected INTEGER, got STRING".
+pub trait FromValue: Sized {
+    fn from_value(value: Object) -> Result<Self, String>;
+}
+
+//Conversion of a Rust value into a Monkey value, used for globals and the
+//results of host functions
+pub trait IntoValue {
+    fn into_value(self) -> Object;
+}
+
+impl FromValue for Object {
+    fn from_value(value: Object) -> Result<Self, String> {
+        Ok(value)
+    }
+}
+
+impl FromValue for i64 {
+    fn from_value(value: Object) -> Result<Self, String> {
+        match value {
+            Object::Integer(value) => Ok(value),
+            #[cfg(feature = "bignum")]
+            Object::BigInteger(value) => Err(format!("expected a 64-bit INTEGER, got {}", value)),
+            _ => Err(expected("INTEGER", &value)),
+        }
+    }
+}
+
+//Integers are accepted as well, like in arithmetic
+impl FromValue for f64 {
+    fn from_value(value: Object) -> Result<Self, String> {
+        match value {
+            Object::Float(value) => Ok(value),
+            Object::Integer(value) => Ok(value as f64),
+            #[cfg(feature = "bignum")]
+            Object::BigInteger(value) => Ok(value.to_f64()),
+            _ => Err(expected("FLOAT", &value)),
+        }
+    }
+}
+
+impl FromValue for bool {
+    fn from_value(value: Object) -> Result<Self, String> {
+        match value {
+            Object::Boolean(value) => Ok(value),
+            _ => Err(expected("BOOLEAN", &value)),
+        }
+    }
+}
+
+impl FromValue for String {
+    fn from_value(value: Object) -> Result<Self, String> {
+        match value {
+            Object::String(value) => Ok(value),
+            _ => Err(expected("STRING", &value)),
+        }
+    }
+}
+
+#[cfg(feature = "bignum")]
+impl FromValue for BigInt {
+    fn from_value(value: Object) -> Result<Self, String> {
+        match value {
+            Object::Integer(value) => Ok(BigInt::from(value)),
+            Object::BigInteger(value) => Ok(value),
+            _ => Err(expected("INTEGER", &value)),
+        }
+    }
+}
+
+impl<T: FromValue> FromValue for Vec<T> {
+    fn from_value(value: Object) -> Result<Self, String> {
+        match value {
+            Object::Array(elements) => elements
+                .into_iter()
+                .enumerate()
+                .map(|(index, element)| {
+                    T::from_value(element).map_err(|e| format!("element {}: {}", index, e))
+                })
+                .collect(),
+            _ => Err(expected("ARRAY", &value)),
+        }
+    }
+}
+
+//null is None
+impl<T: FromValue> FromValue for Option<T> {
+    fn from_value(value: Object) -> Result<Self, String> {
+        match value {
+            Object::Null => Ok(None),
+            value => T::from_value(value).map(Some),
+        }
+    }
+}
+
+impl IntoValue for Object {
+    fn into_value(self) -> Object {
+        self
+    }
+}
+
+impl IntoValue for () {
+    fn into_value(self) -> Object {
+        Object::Null
+    }
+}
+
+impl IntoValue for i64 {
+    fn into_value(self) -> Object {
+        Object::Integer(self)
+    }
+}
+
+impl IntoValue for f64 {
+    fn into_value(self) -> Object {
+        Object::Float(self)
+    }
+}
+
+impl IntoValue for bool {
+    fn into_value(self) -> Object {
+        Object::Boolean(self)
+    }
+}
+
+impl IntoValue for String {
+    fn into_value(self) -> Object {
+        Object::String(self)
+    }
+}
+
+impl IntoValue for &str {
+    fn into_value(self) -> Object {
+        Object::String(self.to_string())
+    }
+}
+
+#[cfg(feature = "bignum")]
+impl IntoValue for BigInt {
+    fn into_value(self) -> Object {
+        Object::from(self)
+    }
+}
+
+impl<T: IntoValue> IntoValue for Vec<T> {
+    fn into_value(self) -> Object {
+        Object::Array(self.into_iter().map(IntoValue::into_value).collect())
+    }
+}
+
+impl<T: IntoValue> IntoValue for Option<T> {
+    fn into_value(self) -> Object {
+        match self {
+            Some(value) => value.into_value(),
+            None => Object::Null,
+        }
+    }
+}
+
+//An Err becomes a runtime error in the script
+impl<T: IntoValue, E: Display> IntoValue for Result<T, E> {
+    fn into_value(self) -> Object {
+        match self {
+            Ok(value) => value.into_value(),
+            Err(e) => Object::Error(e.to_string()),
+        }
+    }
+}
+
+//helper
+fn expected(type_name: &str, value: &Object) -> String {
+    format!("expected {}, got {}", type_name, value.type_name())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_value_test() {
+        assert_eq!(Ok(5), i64::from_value(Object::Integer(5)));
+        assert_eq!(Ok(5.0), f64::from_value(Object::Integer(5)));
+        assert_eq!(
+            Ok(vec![Some(true), None]),
+            Vec::<Option<bool>>::from_value(Object::Array(vec![
+                Object::Boolean(true),
+                Object::Null
+            ]))
+        );
+        assert_eq!(
+            Err("expected INTEGER, got STRING".to_string()),
+            i64::from_value(Object::String("5".to_string()))
+        );
+        assert_eq!(
+            Err("element 1: expected STRING, got BOOLEAN".to_string()),
+            Vec::<String>::from_value(Object::Array(vec![
+                Object::String("a".to_string()),
+                Object::Boolean(false)
+            ]))
+        );
+    }
+
+    #[test]
+    fn into_value_test() {
+        assert_eq!(Object::Null, ().into_value());
+        assert_eq!(Object::String("a".to_string()), "a".into_value());
+        assert_eq!(
+            Object::Array(vec![Object::Integer(1), Object::Null]),
+            vec![Some(1), None].into_value()
+        );
+        assert_eq!(
+            Object::Error("bad input".to_string()),
+            Err::<i64, _>("bad input").into_value()
+        );
+    }
+}
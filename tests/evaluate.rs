@@ -0,0 +1,61 @@
+use std::{cell::RefCell, rc::Rc};
+
+use interpreter_go::{evaluate, parse, Env, Environment, Object};
+
+#[test]
+fn evaluate_test() {
+    let test_inputs = [
+        ("1 + 2 * 3", Object::Integer(7)),
+        ("2.5 * 2", Object::Float(5.0)),
+        ("(0xABCD >> 8) & 0xFF", Object::Integer(0xAB)),
+        ("\"a\" + \"b\"", Object::String("ab".to_string())),
+        (
+            "let fib = fn(n) { if (n < 2) { n } else { fib(n - 1) + fib(n - 2) } }; fib(15)",
+            Object::Integer(610),
+        ),
+        ("[1, 2, 3][1]", Object::Integer(2)),
+        ("int(\"42\")", Object::Integer(42)),
+        ("if (false) { 1 }", Object::Null),
+        ("1 / 0", Object::Error("division by zero".to_string())),
+    ];
+
+    for input in test_inputs.iter() {
+        assert_eq!(input.1, run(input.0, &new_env()), "{}", input.0);
+    }
+}
+
+#[test]
+fn environment_is_shared_test() {
+    let env = new_env();
+    run("let counter = 1;", &env);
+    run("counter += 41;", &env);
+
+    assert_eq!(Some(Object::Integer(42)), env.borrow().get("counter"));
+    assert_eq!(Object::Integer(42), run("counter", &env));
+}
+
+#[test]
+fn host_bindings_test() {
+    let env = new_env();
+    env.borrow_mut().set("limit", Object::Integer(10));
+
+    assert_eq!(Object::Boolean(true), run("limit * 2 > 15", &env));
+}
+
+#[cfg(feature = "bignum")]
+#[test]
+fn big_integer_test() {
+    let result = run("2 ** 100", &new_env());
+
+    assert_eq!("1267650600228229401496703205376", result.to_string());
+    assert_eq!("INTEGER", result.type_name());
+}
+
+//helper
+fn new_env() -> Env {
+    Rc::new(RefCell::new(Environment::new()))
+}
+
+fn run(input: &str, env: &Env) -> Object {
+    evaluate(&parse(input).unwrap(), env)
+}
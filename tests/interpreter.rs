@@ -0,0 +1,48 @@
+use interpreter_go::{parse, FromValue, Interpreter, IntoValue, Object};
+
+//A host type converted from and to a Monkey array
+#[derive(Debug, PartialEq)]
+struct Point(i64, i64);
+
+impl FromValue for Point {
+    fn from_value(value: Object) -> Result<Self, String> {
+        match Vec::<i64>::from_value(value)?.as_slice() {
+            [x, y] => Ok(Point(*x, *y)),
+            other => Err(format!("expected two coordinates, got {}", other.len())),
+        }
+    }
+}
+
+impl IntoValue for Point {
+    fn into_value(self) -> Object {
+        vec![self.0, self.1].into_value()
+    }
+}
+
+#[test]
+fn host_types_test() {
+    let mut interpreter = Interpreter::new();
+    interpreter.register_fn("add", |a: Point, b: Point| Point(a.0 + b.0, a.1 + b.1));
+    interpreter.set_global("origin", Point(0, 0));
+
+    assert_eq!(
+        Point(4, 6).into_value(),
+        interpreter.run("add(add(origin, [1, 2]), [3, 4])").unwrap()
+    );
+    assert_eq!(
+        Object::Error("argument 1 to `add`: expected two coordinates, got 3".to_string()),
+        interpreter.run("add([1, 2, 3], origin)").unwrap()
+    );
+}
+
+#[test]
+fn eval_parsed_programm_test() {
+    let mut interpreter = Interpreter::new();
+    interpreter.set_global("base", 100);
+    interpreter.register_fn("offset", |x: i64| x + 1);
+
+    let programm = parse("let result = offset(base); result * 2").unwrap();
+
+    assert_eq!(Object::Integer(202), interpreter.eval(&programm));
+    assert_eq!(Some(Object::Integer(101)), interpreter.get_global("result"));
+}
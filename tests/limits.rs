@@ -0,0 +1,135 @@
+use std::{cell::RefCell, rc::Rc, time::Duration};
+
+use interpreter_go::{
+    evaluate, parse, Environment, Interpreter, LimitError, Limits, Object, DEFAULT_MAX_CALL_DEPTH,
+    DEFAULT_MAX_EXPRESSION_DEPTH, DEFAULT_MAX_VALUE_DEPTH,
+};
+
+//Untrusted input is stopped with an error instead of overflowing the stack
+#[test]
+fn runaway_recursion_test() {
+    let test_inputs = [
+        (
+            "let f = fn(x) { f(x) }; f(1)",
+            LimitError::CallDepth(DEFAULT_MAX_CALL_DEPTH),
+        ),
+        (
+            "let f = fn(x) { 1 + (1 + (1 + (1 + f(x)))) }; f(1)",
+            LimitError::ExpressionDepth(DEFAULT_MAX_EXPRESSION_DEPTH),
+        ),
+    ];
+
+    for input in test_inputs.iter() {
+        let programm = parse(input.0).unwrap();
+        let env = Rc::new(RefCell::new(Environment::new()));
+
+        assert_eq!(
+            Object::Error(input.1.to_string()),
+            evaluate(&programm, &env),
+            "{}",
+            input.0
+        );
+    }
+}
+
+#[test]
+fn deep_nesting_test() {
+    let input = format!("{}1{}", "(".repeat(100_000), ")".repeat(100_000));
+    let errors = parse(&input).unwrap_err();
+
+    assert_eq!(1, errors.len());
+    assert_eq!(Some("E0007"), errors[0].diagnostic().code.as_deref());
+}
+
+#[test]
+fn long_chain_test() {
+    let test_inputs = [
+        format!("1{}", " + 1".repeat(100_000)),
+        format!("f{}", "()".repeat(100_000)),
+        format!("a{}", "[0]".repeat(100_000)),
+    ];
+
+    for input in test_inputs.iter() {
+        let errors = parse(input).unwrap_err();
+
+        assert_eq!(1, errors.len(), "{}", &input[..10]);
+        assert_eq!(
+            Some("E0007"),
+            errors[0].diagnostic().code.as_deref(),
+            "{}",
+            &input[..10]
+        );
+    }
+}
+
+#[test]
+fn deep_else_if_chain_test() {
+    let input = format!("if (x) {{ 1 }}{}", " else if (x) { 1 }".repeat(100_000));
+    let errors = parse(&input).unwrap_err();
+
+    //A block at the limit recovers on its own, so the chain can be reported
+    //more than once
+    assert!(!errors.is_empty());
+    for error in errors.iter() {
+        assert_eq!(Some("E0007"), error.diagnostic().code.as_deref());
+    }
+}
+
+//Cloning, printing and dropping a value recurse through it
+#[test]
+fn deep_value_test() {
+    let input = format!("let a = []; {}", "a = [a]; ".repeat(20_000));
+    let limits = Limits::new()
+        .with_max_steps(10_000_000)
+        .with_max_allocation(10_000_000);
+    let mut interpreter = Interpreter::new().with_limits(limits);
+
+    assert_eq!(
+        Object::Error(LimitError::ValueDepth(DEFAULT_MAX_VALUE_DEPTH).to_string()),
+        interpreter.run(&input).unwrap()
+    );
+}
+
+#[test]
+fn sandbox_test() {
+    let limits = Limits::new()
+        .with_max_steps(10_000)
+        .with_max_call_depth(64)
+        .with_max_allocation(1 << 10)
+        .with_timeout(Duration::from_secs(5));
+    let mut interpreter = Interpreter::new().with_limits(limits);
+
+    let test_inputs = [
+        (
+            "let loop = fn(n) { loop(n + 1) }; loop(0)",
+            LimitError::CallDepth(64),
+        ),
+        (
+            "let fib = fn(n) { if (n < 2) { n } else { fib(n - 1) + fib(n - 2) } }; fib(25)",
+            LimitError::Steps(10_000),
+        ),
+        (
+            "let grow = fn(s, n) { if (n == 0) { s } else { grow(s + s, n - 1) } }; grow(\"x\", 20)",
+            LimitError::Allocation {
+                size: 2048,
+                max: 1024,
+            },
+        ),
+        (
+            "let f = fn(a, n) { if (n == 0) { a } else { f([a, a], n - 1) } }; f(0, 40)",
+            LimitError::Allocation {
+                size: 2046,
+                max: 1024,
+            },
+        ),
+    ];
+
+    for input in test_inputs.iter() {
+        let result = interpreter.run(input.0).unwrap();
+
+        assert_eq!(Object::Error(input.1.to_string()), result, "{}", input.0);
+        assert_eq!(Some(input.1), interpreter.limit_exceeded(), "{}", input.0);
+    }
+
+    assert_eq!(Object::Integer(55), interpreter.run("fib(10)").unwrap());
+}
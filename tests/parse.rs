@@ -0,0 +1,75 @@
+use interpreter_go::{parse, Expressions, Lexer, Node, Parser, Span, Statements};
+
+#[test]
+fn parse_test() {
+    let programm = parse("let x = 5; x + 1").unwrap();
+
+    assert_eq!(2, programm.statements.len());
+    assert!(matches!(programm.statements[0], Statements::Let(..)));
+    match &programm.statements[1] {
+        Statements::Expression(_, Expressions::InfixExpression(_, left, operator, right)) => {
+            assert_eq!("x", left.token_literal());
+            assert_eq!("+", operator);
+            assert!(matches!(**right, Expressions::IntegerLiteral(_, 1)));
+        }
+        other => panic!("not an infix expression: {:?}", other),
+    }
+}
+
+#[test]
+fn parse_errors_test() {
+    let input = "let = 5; let y = 0b102;";
+    let errors = parse(input).unwrap_err();
+
+    assert_eq!(2, errors.len());
+    assert_eq!(Some("E0001"), errors[0].diagnostic().code.as_deref());
+    assert_eq!(Span::new(21, 22, 1, 22), errors[1].diagnostic().span());
+    assert_eq!(
+        "1:22: Invalid digit 2 in binary literal",
+        errors[1].to_string()
+    );
+    assert!(errors[1]
+        .render(input, false)
+        .contains("not a binary digit"));
+}
+
+#[test]
+fn owned_programm_test() {
+    let programm = {
+        let input = String::from("fn(x) { x * 2 }");
+        parse(&input).unwrap().into_owned()
+    };
+
+    assert_eq!(1, programm.statements.len());
+    match &programm.statements[0] {
+        Statements::Expression(_, Expressions::FunctionLiteral(token, parameters, body, _)) => {
+            assert_eq!("fn", token.literal);
+            assert_eq!(1, parameters.len());
+            assert_eq!("x", parameters[0].value);
+            assert_eq!(Span::new(6, 15, 1, 7), body.span);
+            assert_eq!(1, body.statements.len());
+            match &body.statements[0] {
+                Statements::Expression(
+                    _,
+                    Expressions::InfixExpression(_, left, operator, right),
+                ) => {
+                    assert_eq!("x", left.token_literal());
+                    assert_eq!("*", operator);
+                    assert!(matches!(**right, Expressions::IntegerLiteral(_, 2)));
+                }
+                other => panic!("not an infix expression: {:?}", other),
+            }
+        }
+        other => panic!("not a function literal: {:?}", other),
+    }
+}
+
+#[test]
+fn parser_recovers_test() {
+    let mut parser = Parser::new(Lexer::new("let = 1; let a = 2;"));
+    let programm = parser.parse_programm().unwrap();
+
+    assert_eq!(2, programm.statements.len());
+    assert!(matches!(programm.statements[0], Statements::Error(_)));
+    assert_eq!(1, parser.errors().len());
+}
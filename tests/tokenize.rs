@@ -0,0 +1,55 @@
+use interpreter_go::{tokenize, Lexer, Span, StreamLexer, Token, TokenType, TriviaKind};
+
+#[test]
+fn tokenize_test() {
+    let tokens = tokenize("let x = 0x1F; // done");
+    let expected = [
+        (TokenType::Let, "let"),
+        (TokenType::Ident, "x"),
+        (TokenType::Assign, "="),
+        (TokenType::Int, "0x1F"),
+        (TokenType::Semicolon, ";"),
+        (TokenType::Eof, ""),
+    ];
+
+    assert_eq!(expected.len(), tokens.len());
+    for (token, x) in tokens.iter().zip(expected.iter()) {
+        assert_eq!(x, &(token.token_type, token.literal.as_ref()));
+    }
+    assert_eq!(Span::new(8, 12, 1, 9), tokens[3].span);
+}
+
+#[test]
+fn lexer_errors_test() {
+    let mut lexer = Lexer::new("\"open");
+    while lexer.next_token().token_type != TokenType::Eof {}
+
+    let errors = lexer.take_errors();
+    assert_eq!(1, errors.len());
+    assert_eq!(Some("E0101"), errors[0].code.as_deref());
+}
+
+#[test]
+fn trivia_test() {
+    let mut lexer = Lexer::new("/* a */ x").with_trivia();
+    let token = lexer.next_token();
+
+    let kinds: Vec<TriviaKind> = token.leading_trivia.iter().map(|t| t.kind).collect();
+    assert_eq!(
+        vec![TriviaKind::BlockComment, TriviaKind::Whitespace],
+        kinds
+    );
+}
+
+#[test]
+fn stream_lexer_test() {
+    let input = "let add = fn(a, b) { a + b }; add(1.5, 2e3) - 0.25E-1";
+
+    for chunk_size in 1..=input.len() {
+        let tokens: Vec<Token> = StreamLexer::with_chunk_size(input.as_bytes(), chunk_size)
+            .collect::<std::io::Result<_>>()
+            .unwrap();
+
+        assert_eq!(tokenize(input), tokens, "chunk size {}", chunk_size);
+    }
+}